@@ -0,0 +1,331 @@
+use std::fmt::Write;
+
+use crate::{
+    interpreter::RuntimeError,
+    parser::{ParseError, ParseErrorKind},
+    resolver::ResolveError,
+    scanner::{ScanError, ScanErrorKind},
+    span::Span,
+    token_kind::TokenKind,
+};
+
+const RED: &str = "1;31";
+const YELLOW: &str = "1;33";
+const BLUE: &str = "1;34";
+const BOLD: &str = "1";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl Severity {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        }
+    }
+
+    fn color(&self) -> &'static str {
+        match self {
+            Severity::Error => RED,
+            Severity::Warning => YELLOW,
+        }
+    }
+}
+
+/// The stage of running a program that found a problem.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Phase {
+    Scan,
+    Parse,
+    Resolve,
+    Runtime,
+}
+
+impl Phase {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Phase::Scan => "scan",
+            Phase::Parse => "parse",
+            Phase::Resolve => "resolve",
+            Phase::Runtime => "runtime",
+        }
+    }
+}
+
+/// How diagnostics are written to stderr.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ErrorFormat {
+    /// Rendered for people, with source snippets.
+    #[default]
+    Human,
+    /// One JSON object per line, for tools.
+    Json,
+}
+
+impl ErrorFormat {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "human" => Some(ErrorFormat::Human),
+            "json" => Some(ErrorFormat::Json),
+            _ => None,
+        }
+    }
+}
+
+/// A problem found in a Lox program, in a form every phase can share and that
+/// can be rendered against the source it came from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub phase: Phase,
+    /// A stable, kebab-case name for the kind of problem.
+    pub code: &'static str,
+    pub message: String,
+    pub span: Option<Span>,
+    pub notes: Vec<String>,
+    pub help: Option<String>,
+}
+
+impl Diagnostic {
+    pub fn error(phase: Phase, code: &'static str, message: String, span: Option<Span>) -> Self {
+        Diagnostic {
+            severity: Severity::Error,
+            phase,
+            code,
+            message,
+            span,
+            notes: Vec::new(),
+            help: None,
+        }
+    }
+
+    pub fn with_note(mut self, note: &str) -> Self {
+        self.notes.push(note.to_string());
+        self
+    }
+
+    pub fn with_help(mut self, help: &str) -> Self {
+        self.help = Some(help.to_string());
+        self
+    }
+
+    /// Renders the diagnostic with the location, the offending source line
+    /// and a caret underline, using ANSI colours when `color` is set.
+    ///
+    /// The snippet is left out when the span does not fall inside `source`.
+    pub fn render(&self, file_name: &str, source: &str, color: bool) -> String {
+        let paint = |text: &str, style: &str| {
+            if color {
+                format!("\x1b[{}m{}\x1b[0m", style, text)
+            } else {
+                text.to_string()
+            }
+        };
+
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "{}: {}",
+            paint(self.severity.label(), self.severity.color()),
+            paint(&self.message, BOLD)
+        );
+
+        let line_number = self
+            .span
+            .map_or(String::new(), |span| span.line.to_string());
+        let gutter = " ".repeat(line_number.len());
+
+        match self.span {
+            Some(span) => {
+                let _ = writeln!(
+                    out,
+                    "{}{} {}:{}:{}",
+                    gutter,
+                    paint("-->", BLUE),
+                    file_name,
+                    span.line,
+                    span.column
+                );
+                if let Some((line, indent, width)) = snippet(source, span) {
+                    let bar = paint("|", BLUE);
+                    let _ = writeln!(out, "{} {}", gutter, bar);
+                    let _ = writeln!(out, "{} {} {}", paint(&line_number, BLUE), bar, line);
+                    let _ = writeln!(
+                        out,
+                        "{} {} {}{}",
+                        gutter,
+                        bar,
+                        indent,
+                        paint(&"^".repeat(width), self.severity.color())
+                    );
+                }
+            }
+            None => {
+                let _ = writeln!(out, "{} {}", paint("-->", BLUE), file_name);
+            }
+        }
+
+        for note in &self.notes {
+            let _ = writeln!(out, "{} {} note: {}", gutter, paint("=", BLUE), note);
+        }
+        if let Some(help) = &self.help {
+            let _ = writeln!(out, "{} {} help: {}", gutter, paint("=", BLUE), help);
+        }
+
+        out
+    }
+
+    /// Formats the diagnostic as a single-line JSON object. Location fields
+    /// are `null` when the diagnostic has no span.
+    pub fn to_json(&self, file_name: &str) -> String {
+        let mut out = String::new();
+        let _ = write!(
+            out,
+            "{{\"severity\":{},\"phase\":{},\"code\":{},\"message\":{},\"file\":{}",
+            json_string(self.severity.label()),
+            json_string(self.phase.label()),
+            json_string(self.code),
+            json_string(&self.message),
+            json_string(file_name)
+        );
+        match self.span {
+            Some(span) => {
+                let _ = write!(
+                    out,
+                    ",\"line\":{},\"column\":{},\"span\":{{\"start\":{},\"end\":{}}}",
+                    span.line, span.column, span.start, span.end
+                );
+            }
+            None => out.push_str(",\"line\":null,\"column\":null,\"span\":null"),
+        }
+
+        let notes: Vec<_> = self.notes.iter().map(|note| json_string(note)).collect();
+        let _ = write!(out, ",\"notes\":[{}]", notes.join(","));
+        match &self.help {
+            Some(help) => {
+                let _ = write!(out, ",\"help\":{}}}", json_string(help));
+            }
+            None => out.push_str(",\"help\":null}"),
+        }
+        out
+    }
+}
+
+fn json_string(text: &str) -> String {
+    let mut out = String::with_capacity(text.len() + 2);
+    out.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Finds the source line holding the start of `span`, the whitespace needed
+/// to line a caret up under it, and how many carets to draw. Spans running
+/// past the end of the line are underlined to the end of the line.
+fn snippet(source: &str, span: Span) -> Option<(&str, String, usize)> {
+    if span.start > source.len() || !source.is_char_boundary(span.start) {
+        return None;
+    }
+
+    let line_start = source[..span.start].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = source[span.start..]
+        .find('\n')
+        .map_or(source.len(), |i| span.start + i);
+    let line = source[line_start..line_end].trim_end_matches('\r');
+
+    let indent = source[line_start..span.start]
+        .chars()
+        .map(|c| if c == '\t' { '\t' } else { ' ' })
+        .collect();
+    let underlined = span.end.clamp(span.start, line_end);
+    let width = source
+        .get(span.start..underlined)
+        .map_or(0, |text| text.chars().count())
+        .max(1);
+
+    Some((line, indent, width))
+}
+
+impl From<ScanError> for Diagnostic {
+    fn from(err: ScanError) -> Self {
+        let diagnostic = Diagnostic::error(
+            Phase::Scan,
+            err.kind.code(),
+            err.kind.message().to_string(),
+            Some(err.span()),
+        );
+        match err.kind {
+            ScanErrorKind::UnterminatedString => {
+                diagnostic.with_help("add a closing '\"' to end the string")
+            }
+            ScanErrorKind::UnterminatedComment => {
+                diagnostic.with_help("add a closing '*/' to end the comment")
+            }
+            ScanErrorKind::InvalidEscape => diagnostic
+                .with_help("valid escapes are \\n, \\t, \\r, \\\\, \\\", \\0, \\$ and \\u{XXXX}"),
+            ScanErrorKind::InvalidUnicodeEscape => {
+                diagnostic.with_help("write 1 to 6 hex digits naming a character, as in \\u{1F600}")
+            }
+            ScanErrorKind::MalformedNumber => {
+                diagnostic.with_help("numbers look like 42, 3.14, 6.02e23, 1_000, 0xFF or 0b1010")
+            }
+            _ => diagnostic,
+        }
+    }
+}
+
+impl From<ParseError> for Diagnostic {
+    fn from(err: ParseError) -> Self {
+        let diagnostic = Diagnostic::error(
+            Phase::Parse,
+            err.kind.code(),
+            err.message,
+            Some(err.token.span()),
+        );
+        match err.kind {
+            ParseErrorKind::InvalidAssignmentTarget => {
+                diagnostic.with_note("only variables, fields and elements can be assigned to")
+            }
+            ParseErrorKind::ExpectedToken(TokenKind::Semicolon)
+                if err.token.kind == TokenKind::Eof =>
+            {
+                diagnostic.with_note("the program ended before the statement was finished")
+            }
+            _ => diagnostic,
+        }
+    }
+}
+
+impl From<ResolveError> for Diagnostic {
+    fn from(err: ResolveError) -> Self {
+        Diagnostic::error(
+            Phase::Resolve,
+            err.kind.code(),
+            err.message,
+            Some(err.token.span()),
+        )
+    }
+}
+
+impl From<RuntimeError> for Diagnostic {
+    fn from(err: RuntimeError) -> Self {
+        let span = err.token.as_ref().map(|token| token.span());
+        Diagnostic::error(Phase::Runtime, err.kind.code(), err.message, span)
+    }
+}
@@ -1,80 +1,95 @@
-use std::collections::HashMap;
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
 
-use crate::{interpreter::RuntimeError, literal::Literal, token::Token};
+use crate::{
+    interpreter::{RuntimeError, RuntimeErrorKind},
+    literal::Literal,
+    token::Token,
+};
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Environment {
-    pub enclosing: Option<Box<Environment>>,
+    pub enclosing: Option<Rc<RefCell<Environment>>>,
     values: HashMap<String, Literal>,
 }
 
 impl Environment {
-    pub fn new(enclosing: Option<Box<Environment>>) -> Self {
+    pub fn new(enclosing: Option<Rc<RefCell<Environment>>>) -> Self {
         Environment {
             enclosing,
             values: HashMap::new(),
         }
     }
 
+    pub fn values(&self) -> &HashMap<String, Literal> {
+        &self.values
+    }
+
     pub fn define(&mut self, name: String, value: Literal) {
         self.values.insert(name, value);
     }
 
-    pub fn ancestor(&self, distance: usize) -> Option<Box<Environment>> {
-        if distance == 0 {
-            return Some(Box::new(self.clone()));
-        }
-        let mut environment = self;
-        for _ in 0..distance {
-            match &environment.enclosing {
-                Some(enclosing) => environment = enclosing,
-                None => return None,
-            }
+    /// Returns the environment `distance` scopes out from this one. An
+    /// environment doesn't hold the `Rc` it lives in, so `distance` must be at
+    /// least 1; callers handle distance 0 with `self`.
+    fn ancestor(&self, distance: usize) -> Option<Rc<RefCell<Environment>>> {
+        debug_assert!(distance >= 1, "distance 0 is the environment itself");
+        let mut environment = self.enclosing.clone()?;
+        for _ in 1..distance {
+            let enclosing = environment.borrow().enclosing.clone()?;
+            environment = enclosing;
         }
-        Some(Box::new(environment.clone()))
+        Some(environment)
     }
 
-    pub fn get_at(self, distance: usize, name: String) -> Option<Literal> {
-        match self.ancestor(distance) {
-            Some(env) => env.values.get(&name).cloned(),
-            None => None,
+    pub fn get_at(&self, distance: usize, name: &str) -> Option<Literal> {
+        if distance == 0 {
+            return self.values.get(name).cloned();
         }
+        let ancestor = self.ancestor(distance)?;
+        let value = ancestor.borrow().values.get(name).cloned();
+        value
     }
 
     pub fn assign_at(&mut self, distance: usize, name: Token, value: Literal) {
-        match self.ancestor(distance) {
-            Some(mut env) => env.values.insert(name.lexeme, value),
-            None => None,
-        };
+        if distance == 0 {
+            self.values.insert(name.lexeme().to_string(), value);
+        } else if let Some(ancestor) = self.ancestor(distance) {
+            ancestor
+                .borrow_mut()
+                .values
+                .insert(name.lexeme().to_string(), value);
+        }
     }
 
     pub fn get(&self, name: Token) -> Result<Literal, RuntimeError> {
-        if let Some(value) = self.values.get(&name.lexeme) {
+        if let Some(value) = self.values.get(name.lexeme()) {
             return Ok(value.clone());
         }
 
         if let Some(enclosing) = &self.enclosing {
-            return enclosing.get(name);
+            return enclosing.borrow().get(name);
         }
 
         Err(RuntimeError::new(
-            format!("Undefined variable '{}'.", name.lexeme),
+            RuntimeErrorKind::UndefinedVariable,
+            format!("Undefined variable '{}'.", name.lexeme()),
             Some(name),
         ))
     }
 
     pub fn assign(&mut self, name: Token, value: Literal) -> Result<(), RuntimeError> {
-        if self.values.contains_key(&name.lexeme) {
-            self.values.insert(name.lexeme.clone(), value);
+        if self.values.contains_key(name.lexeme()) {
+            self.values.insert(name.lexeme().to_string(), value);
             return Ok(());
         }
 
-        if !self.enclosing.is_none() {
-            return self.enclosing.as_mut().unwrap().assign(name, value);
+        if let Some(enclosing) = &self.enclosing {
+            return enclosing.borrow_mut().assign(name, value);
         }
 
         Err(RuntimeError::new(
-            format!("Undefined variable '{}'.", name.lexeme),
+            RuntimeErrorKind::UndefinedVariable,
+            format!("Undefined variable '{}'.", name.lexeme()),
             Some(name),
         ))
     }
@@ -1,225 +1,641 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
 use crate::{
-    environment::Environment, interpreter::RuntimeError, literal::Literal, token::Token,
+    class::LoxInstance,
+    function::{Callable, LoxFunction},
+    interpreter::{Interpreter, RuntimeError, RuntimeErrorKind},
+    literal::Literal,
+    map::{LoxMap, MapKey},
+    span::Span,
+    statement::Stmt,
+    token::Token,
     token_kind::TokenKind,
 };
 
+/// Identifies a variable reference so the resolver can record its scope depth.
+pub type ExprId = usize;
+
+static NEXT_EXPR_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// Returns an id that is unique for the lifetime of the process, so
+/// resolutions from earlier parses stay valid alongside later ones.
+pub fn next_expr_id() -> ExprId {
+    NEXT_EXPR_ID.fetch_add(1, Ordering::Relaxed)
+}
+
 #[derive(Debug, Clone)]
 pub enum Expr {
-    Literal(Literal),
-    Variable(Token),
-    Assign(Token, Box<Expr>),
+    Literal(Literal, Span),
+    Variable(Token, ExprId),
+    Assign(Token, Box<Expr>, ExprId),
     Binary(Box<Expr>, Token, Box<Expr>),
     Logical(Box<Expr>, Token, Box<Expr>),
     Unary(Token, Box<Expr>),
-    Grouping(Box<Expr>),
+    Grouping(Box<Expr>, Span),
     Call(Box<Expr>, Token, Vec<Expr>),
+    Get(Box<Expr>, Token),
+    /// `object?.name`, which is nil when the object is nil.
+    OptionalGet(Box<Expr>, Token),
+    /// A chain of calls, property reads and indexes containing `?.`. A nil
+    /// receiver of any `?.` makes the whole chain nil, so `a?.b.c()` skips
+    /// `.c()` as well.
+    OptionalChain(Box<Expr>),
+    Set(Box<Expr>, Token, Box<Expr>),
+    This(Token, ExprId),
+    Super(Token, Token, ExprId),
+    /// An anonymous `fun (params) { body }`, with its `fun` keyword.
+    Lambda(Token, Vec<Token>, Rc<Vec<Stmt>>, Span),
+    /// `condition ? then : else`.
+    Conditional(Box<Expr>, Box<Expr>, Box<Expr>),
+    /// `[a, b, c]`.
+    List(Vec<Expr>, Span),
+    /// `{key: value, ...}`, with the colon of each entry.
+    Map(Vec<(Expr, Token, Expr)>, Span),
+    /// `object[index]`, with the closing bracket.
+    Index(Box<Expr>, Box<Expr>, Token),
+    /// `object[index] = value`, with the closing bracket.
+    SetIndex(Box<Expr>, Box<Expr>, Token, Box<Expr>),
+    /// `target op= value` for a variable, property or element target, where
+    /// `++` and `--` update by a literal `1`. The flag marks postfix `++` and
+    /// `--`, which evaluate to the value from before the update.
+    Update(Box<Expr>, Token, Box<Expr>, bool),
+    /// A string with embedded expressions, as literal and expression parts.
+    Interpolation(Vec<Expr>, Span),
+}
+
+impl Expr {
+    /// Returns the span of source text this expression was parsed from.
+    pub fn span(&self) -> Span {
+        match self {
+            Expr::Literal(_, span)
+            | Expr::Grouping(_, span)
+            | Expr::Interpolation(_, span)
+            | Expr::List(_, span)
+            | Expr::Map(_, span)
+            | Expr::Lambda(_, _, _, span) => *span,
+            Expr::OptionalChain(chain) => chain.span(),
+            Expr::Variable(name, _) => name.span(),
+            Expr::Assign(name, value, _) => name.span().to(value.span()),
+            Expr::Binary(left, _, right) | Expr::Logical(left, _, right) => {
+                left.span().to(right.span())
+            }
+            Expr::Unary(operator, right) => operator.span().to(right.span()),
+            Expr::Call(callee, paren, _) => callee.span().to(paren.span()),
+            Expr::Get(object, name) | Expr::OptionalGet(object, name) => {
+                object.span().to(name.span())
+            }
+            Expr::Conditional(condition, _, else_branch) => condition.span().to(else_branch.span()),
+            Expr::Set(object, _, value) | Expr::SetIndex(object, _, _, value) => {
+                object.span().to(value.span())
+            }
+            Expr::Index(object, _, bracket) => object.span().to(bracket.span()),
+            Expr::This(keyword, _) => keyword.span(),
+            Expr::Super(keyword, method, _) => keyword.span().to(method.span()),
+            Expr::Update(target, operator, value, _) => {
+                if operator.start < target.span().start {
+                    operator.span().to(target.span())
+                } else {
+                    target.span().to(operator.span()).to(value.span())
+                }
+            }
+        }
+    }
 }
 
 pub trait Evaluate {
-    fn evaluate(&self, environment: &mut Environment) -> Result<Literal, RuntimeError>;
+    fn evaluate(&self, interpreter: &mut Interpreter) -> Result<Literal, RuntimeError>;
 }
 impl Evaluate for Expr {
-    fn evaluate(&self, environment: &mut Environment) -> Result<Literal, RuntimeError> {
+    // Each variant with locals of its own is evaluated by a separate function
+    // so that this frame, which every nested evaluation and Lox call passes
+    // through, stays small in unoptimized builds.
+    fn evaluate(&self, interpreter: &mut Interpreter) -> Result<Literal, RuntimeError> {
         match self {
-            Expr::Literal(literal) => Ok(literal.clone()),
-            Expr::Variable(token) => environment.get(token.clone()),
-            Expr::Assign(token, expr) => {
-                let value = expr.evaluate(environment)?;
-                environment.assign(token.clone(), value.clone())?;
-                Ok(value)
+            Expr::Literal(literal, _) => Ok(literal.clone()),
+            Expr::Variable(token, id) => interpreter.look_up_variable(token, *id),
+            Expr::Assign(token, expr, id) => assign(interpreter, token, expr, *id),
+            Expr::Binary(left, token, right) => binary_expr(interpreter, left, token, right),
+            Expr::Logical(left, token, right) => logical(interpreter, left, token, right),
+            Expr::Unary(token, expr) => unary(interpreter, token, expr),
+            Expr::Grouping(expr, _) => expr.evaluate(interpreter),
+            Expr::OptionalChain(chain) => optional_chain_expr(interpreter, chain),
+            Expr::Lambda(_, params, body, _) => Ok(lambda(interpreter, params, body)),
+            Expr::Conditional(condition, then_branch, else_branch) => {
+                conditional(interpreter, condition, then_branch, else_branch)
             }
-            Expr::Binary(left, token, right) => match token.kind {
-                TokenKind::Plus => {
-                    let left = left.evaluate(environment)?;
-                    let right = right.evaluate(environment)?;
-                    match (left, right) {
-                        (Literal::Number(left), Literal::Number(right)) => {
-                            return Ok(Literal::Number(left + right))
-                        }
-                        (Literal::String(left), Literal::String(right)) => {
-                            return Ok(Literal::String(format!("{}{}", left, right)))
-                        }
-                        (Literal::String(left), Literal::Number(right)) => {
-                            return Ok(Literal::String(format!("{}{}", left, right)))
-                        }
-                        (Literal::Number(left), Literal::String(right)) => {
-                            return Ok(Literal::String(format!("{}{}", left, right)))
-                        }
-                        _ => {
-                            return Err(RuntimeError::new(
-                                "Operands must be numbers or strings".to_string(),
-                                Some(token.clone()),
-                            ))
-                        }
-                    }
-                }
-                TokenKind::Minus => {
-                    let left = left.evaluate(environment)?;
-                    let right = right.evaluate(environment)?;
-                    match (left, right) {
-                        (Literal::Number(left), Literal::Number(right)) => {
-                            return Ok(Literal::Number(left - right))
-                        }
-                        _ => {
-                            return Err(RuntimeError::new(
-                                "Operands must be two numbers".to_string(),
-                                Some(token.clone()),
-                            ))
-                        }
-                    }
-                }
-                TokenKind::Slash => {
-                    let left = left.evaluate(environment)?;
-                    let right = right.evaluate(environment)?;
-                    match (left, right) {
-                        (Literal::Number(left), Literal::Number(right)) => {
-                            return Ok(Literal::Number(left / right))
-                        }
-                        _ => {
-                            return Err(RuntimeError::new(
-                                "Operands must be two numbers".to_string(),
-                                Some(token.clone()),
-                            ))
-                        }
-                    }
-                }
-                TokenKind::Star => {
-                    let left = left.evaluate(environment)?;
-                    let right = right.evaluate(environment)?;
-                    match (left, right) {
-                        (Literal::Number(left), Literal::Number(right)) => {
-                            return Ok(Literal::Number(left * right))
-                        }
-                        _ => {
-                            return Err(RuntimeError::new(
-                                "Operands must be two numbers".to_string(),
-                                Some(token.clone()),
-                            ))
-                        }
-                    }
-                }
-                TokenKind::Greater => {
-                    let left = left.evaluate(environment)?;
-                    let right = right.evaluate(environment)?;
-                    match (left, right) {
-                        (Literal::Number(left), Literal::Number(right)) => {
-                            return Ok(Literal::Boolean(left > right))
-                        }
-                        _ => {
-                            return Err(RuntimeError::new(
-                                "Operands must be two numbers".to_string(),
-                                Some(token.clone()),
-                            ))
-                        }
-                    }
-                }
-                TokenKind::GreaterEqual => {
-                    let left = left.evaluate(environment)?;
-                    let right = right.evaluate(environment)?;
-                    match (left, right) {
-                        (Literal::Number(left), Literal::Number(right)) => {
-                            return Ok(Literal::Boolean(left >= right))
-                        }
-                        _ => {
-                            return Err(RuntimeError::new(
-                                "Operands must be two numbers".to_string(),
-                                Some(token.clone()),
-                            ))
-                        }
-                    }
-                }
-                TokenKind::Less => {
-                    let left = left.evaluate(environment)?;
-                    let right = right.evaluate(environment)?;
-                    match (left, right) {
-                        (Literal::Number(left), Literal::Number(right)) => {
-                            return Ok(Literal::Boolean(left < right))
-                        }
-                        _ => {
-                            return Err(RuntimeError::new(
-                                "Operands must be two numbers".to_string(),
-                                Some(token.clone()),
-                            ))
-                        }
-                    }
-                }
-                TokenKind::LessEqual => {
-                    let left = left.evaluate(environment)?;
-                    let right = right.evaluate(environment)?;
-                    match (left, right) {
-                        (Literal::Number(left), Literal::Number(right)) => {
-                            return Ok(Literal::Boolean(left <= right))
-                        }
-                        _ => {
-                            return Err(RuntimeError::new(
-                                "Operands must be two numbers".to_string(),
-                                Some(token.clone()),
-                            ))
-                        }
-                    }
-                }
-                TokenKind::EqualEqual => {
-                    let left = left.evaluate(environment)?;
-                    let right = right.evaluate(environment)?;
-                    return Ok(Literal::Boolean(left == right));
-                }
-                TokenKind::BangEqual => {
-                    let left = left.evaluate(environment)?;
-                    let right = right.evaluate(environment)?;
-                    return Ok(Literal::Boolean(left != right));
-                }
-                _ => panic!("Invalid binary operator"),
-            },
-            Expr::Logical(left, token, right) => {
-                let left = left.evaluate(environment)?;
-                match token.kind {
-                    TokenKind::Or => {
-                        if left.is_truthy() {
-                            Ok(Literal::Boolean(true))
-                        } else {
-                            let right = right.evaluate(environment)?;
-                            Ok(right)
-                        }
-                    }
-                    TokenKind::And => {
-                        if !left.is_truthy() {
-                            Ok(Literal::Boolean(false))
-                        } else {
-                            let right = right.evaluate(environment)?;
-                            Ok(right)
-                        }
-                    }
-                    _ => Err(RuntimeError::new(
-                        "Logical evaluator needs to be AND or OR".to_string(),
-                        Some(token.clone()),
-                    )),
-                }
+            Expr::Update(target, operator, value, postfix) => {
+                update_target(interpreter, target, operator, value, *postfix)
             }
-            Expr::Unary(token, expr) => {
-                let literal = expr.evaluate(environment)?;
-                match token.kind {
-                    TokenKind::Bang => Ok(Literal::Boolean(!literal.is_truthy())),
-                    TokenKind::Minus => {
-                        if let Literal::Number(n) = literal {
-                            Ok(Literal::Number(-n))
-                        } else {
-                            Err(RuntimeError::new(
-                                "Operand must be a number.".to_string(),
-                                Some(token.clone()),
-                            ))
-                        }
-                    }
-                    _ => Err(RuntimeError::new(
-                        "Unary Unreachable".to_string(),
-                        Some(token.clone()),
-                    )),
-                }
+            Expr::Interpolation(parts, _) => interpolation(interpreter, parts),
+            Expr::Call(callee, paren, arguments) => {
+                call_expr(interpreter, callee, paren, arguments)
+            }
+            Expr::OptionalGet(object, name) => get(interpreter, object, name, true),
+            Expr::Get(object, name) => get(interpreter, object, name, false),
+            Expr::Set(object, name, value) => set_property(interpreter, object, name, value),
+            Expr::List(elements, _) => list(interpreter, elements),
+            Expr::Map(entries, _) => map(interpreter, entries),
+            Expr::Index(object, index, bracket) => read_index(interpreter, object, index, bracket),
+            Expr::SetIndex(object, index, bracket, value) => {
+                assign_index(interpreter, object, index, bracket, value)
+            }
+            Expr::This(keyword, id) => interpreter.look_up_variable(keyword, *id),
+            Expr::Super(keyword, method, id) => interpreter.look_up_super(keyword, method, *id),
+        }
+    }
+}
+
+fn binary_expr(
+    interpreter: &mut Interpreter,
+    left: &Expr,
+    operator: &Token,
+    right: &Expr,
+) -> Result<Literal, RuntimeError> {
+    let left = left.evaluate(interpreter)?;
+    let right = right.evaluate(interpreter)?;
+    binary(operator.kind, operator, left, right)
+}
+
+fn optional_chain_expr(
+    interpreter: &mut Interpreter,
+    chain: &Expr,
+) -> Result<Literal, RuntimeError> {
+    Ok(optional_chain(chain, interpreter)?.unwrap_or(Literal::Nil))
+}
+
+fn conditional(
+    interpreter: &mut Interpreter,
+    condition: &Expr,
+    then_branch: &Expr,
+    else_branch: &Expr,
+) -> Result<Literal, RuntimeError> {
+    if condition.evaluate(interpreter)?.is_truthy() {
+        then_branch.evaluate(interpreter)
+    } else {
+        else_branch.evaluate(interpreter)
+    }
+}
+
+fn call_expr(
+    interpreter: &mut Interpreter,
+    callee: &Expr,
+    paren: &Token,
+    arguments: &[Expr],
+) -> Result<Literal, RuntimeError> {
+    let callee = callee.evaluate(interpreter)?;
+    call(interpreter, callee, paren, arguments)
+}
+
+/// Reads `object.name`, or with `optional` set, `object?.name`, which is nil
+/// for a nil object.
+fn get(
+    interpreter: &mut Interpreter,
+    object: &Expr,
+    name: &Token,
+    optional: bool,
+) -> Result<Literal, RuntimeError> {
+    match object.evaluate(interpreter)? {
+        Literal::Nil if optional => Ok(Literal::Nil),
+        object => get_property(object, name),
+    }
+}
+
+fn assign(
+    interpreter: &mut Interpreter,
+    name: &Token,
+    value: &Expr,
+    id: ExprId,
+) -> Result<Literal, RuntimeError> {
+    let value = value.evaluate(interpreter)?;
+    interpreter.assign_variable(name, id, value.clone())?;
+    Ok(value)
+}
+
+fn logical(
+    interpreter: &mut Interpreter,
+    left: &Expr,
+    token: &Token,
+    right: &Expr,
+) -> Result<Literal, RuntimeError> {
+    let left = left.evaluate(interpreter)?;
+    match token.kind {
+        TokenKind::Or => {
+            if left.is_truthy() {
+                Ok(Literal::Boolean(true))
+            } else {
+                let right = right.evaluate(interpreter)?;
+                Ok(right)
             }
-            Expr::Grouping(expr) => expr.evaluate(environment),
-            Expr::Call(callee, token, arguments) => {
-                // Implement function call evaluation logic here
-                unimplemented!("Function call evaluation is not implemented yet")
+        }
+        TokenKind::And => {
+            if !left.is_truthy() {
+                Ok(Literal::Boolean(false))
+            } else {
+                let right = right.evaluate(interpreter)?;
+                Ok(right)
             }
         }
+        TokenKind::QuestionQuestion => match left {
+            Literal::Nil => right.evaluate(interpreter),
+            left => Ok(left),
+        },
+        _ => Err(RuntimeError::new(
+            RuntimeErrorKind::Internal,
+            "Logical evaluator needs to be AND or OR".to_string(),
+            Some(token.clone()),
+        )),
+    }
+}
+
+fn unary(
+    interpreter: &mut Interpreter,
+    token: &Token,
+    expr: &Expr,
+) -> Result<Literal, RuntimeError> {
+    let literal = expr.evaluate(interpreter)?;
+    match token.kind {
+        TokenKind::Bang => Ok(Literal::Boolean(!literal.is_truthy())),
+        TokenKind::Minus => {
+            if let Literal::Number(n) = literal {
+                Ok(Literal::Number(-n))
+            } else {
+                Err(RuntimeError::new(
+                    RuntimeErrorKind::InvalidOperand,
+                    "Operand must be a number.".to_string(),
+                    Some(token.clone()),
+                ))
+            }
+        }
+        _ => Err(RuntimeError::new(
+            RuntimeErrorKind::Internal,
+            "Unary Unreachable".to_string(),
+            Some(token.clone()),
+        )),
+    }
+}
+
+fn lambda(interpreter: &Interpreter, params: &[Token], body: &Rc<Vec<Stmt>>) -> Literal {
+    Literal::Function(Rc::new(LoxFunction::new(
+        None,
+        params.to_vec(),
+        Rc::clone(body),
+        Rc::clone(&interpreter.environment),
+        false,
+    )))
+}
+
+fn update_target(
+    interpreter: &mut Interpreter,
+    target: &Expr,
+    operator: &Token,
+    value: &Expr,
+    postfix: bool,
+) -> Result<Literal, RuntimeError> {
+    let (old, new) = match target {
+        Expr::Variable(name, id) => {
+            let old = interpreter.look_up_variable(name, *id)?;
+            let new = update(operator, old.clone(), value.evaluate(interpreter)?)?;
+            interpreter.assign_variable(name, *id, new.clone())?;
+            (old, new)
+        }
+        Expr::Get(object, name) => {
+            let Literal::Instance(instance) = object.evaluate(interpreter)? else {
+                return Err(RuntimeError::new(
+                    RuntimeErrorKind::NotAnInstance,
+                    "Only instances have fields.".to_string(),
+                    Some(name.clone()),
+                ));
+            };
+            let old = LoxInstance::get(&instance, name)?;
+            let new = update(operator, old.clone(), value.evaluate(interpreter)?)?;
+            instance.borrow_mut().set(name, new.clone());
+            (old, new)
+        }
+        Expr::Index(object, index, bracket) => {
+            let object = object.evaluate(interpreter)?;
+            let index = index.evaluate(interpreter)?;
+            let old = get_index(&object, &index, bracket)?;
+            let new = update(operator, old.clone(), value.evaluate(interpreter)?)?;
+            set_index(&object, &index, new.clone(), bracket)?;
+            (old, new)
+        }
+        _ => unreachable!("the parser only updates variables, properties and elements"),
+    };
+    Ok(if postfix { old } else { new })
+}
+
+fn interpolation(interpreter: &mut Interpreter, parts: &[Expr]) -> Result<Literal, RuntimeError> {
+    let mut text = String::new();
+    for part in parts {
+        // Values are stringified the same way `print` does.
+        text.push_str(&part.evaluate(interpreter)?.to_string());
+    }
+    Ok(Literal::String(text))
+}
+
+fn set_property(
+    interpreter: &mut Interpreter,
+    object: &Expr,
+    name: &Token,
+    value: &Expr,
+) -> Result<Literal, RuntimeError> {
+    let Literal::Instance(instance) = object.evaluate(interpreter)? else {
+        return Err(RuntimeError::new(
+            RuntimeErrorKind::NotAnInstance,
+            "Only instances have fields.".to_string(),
+            Some(name.clone()),
+        ));
+    };
+    let value = value.evaluate(interpreter)?;
+    instance.borrow_mut().set(name, value.clone());
+    Ok(value)
+}
+
+fn list(interpreter: &mut Interpreter, elements: &[Expr]) -> Result<Literal, RuntimeError> {
+    let mut values = Vec::with_capacity(elements.len());
+    for element in elements {
+        values.push(element.evaluate(interpreter)?);
+    }
+    Ok(Literal::List(Rc::new(RefCell::new(values))))
+}
+
+fn map(
+    interpreter: &mut Interpreter,
+    entries: &[(Expr, Token, Expr)],
+) -> Result<Literal, RuntimeError> {
+    let mut map = LoxMap::new();
+    for (key, colon, value) in entries {
+        let key = map_key(&key.evaluate(interpreter)?, colon)?;
+        map.insert(key, value.evaluate(interpreter)?);
+    }
+    Ok(Literal::Map(Rc::new(RefCell::new(map))))
+}
+
+fn read_index(
+    interpreter: &mut Interpreter,
+    object: &Expr,
+    index: &Expr,
+    bracket: &Token,
+) -> Result<Literal, RuntimeError> {
+    let object = object.evaluate(interpreter)?;
+    let index = index.evaluate(interpreter)?;
+    get_index(&object, &index, bracket)
+}
+
+fn assign_index(
+    interpreter: &mut Interpreter,
+    object: &Expr,
+    index: &Expr,
+    bracket: &Token,
+    value: &Expr,
+) -> Result<Literal, RuntimeError> {
+    let object = object.evaluate(interpreter)?;
+    let index = index.evaluate(interpreter)?;
+    let value = value.evaluate(interpreter)?;
+    set_index(&object, &index, value.clone(), bracket)?;
+    Ok(value)
+}
+
+/// Calls the evaluated `callee` with `arguments`, reporting errors at the
+/// call's closing `paren`.
+fn call(
+    interpreter: &mut Interpreter,
+    callee: Literal,
+    paren: &Token,
+    arguments: &[Expr],
+) -> Result<Literal, RuntimeError> {
+    let mut values = Vec::with_capacity(arguments.len());
+    for argument in arguments {
+        values.push(argument.evaluate(interpreter)?);
+    }
+
+    let callable: &dyn Callable = match &callee {
+        Literal::Function(function) => function.as_ref(),
+        Literal::Class(class) => class,
+        _ => {
+            return Err(RuntimeError::new(
+                RuntimeErrorKind::NotCallable,
+                "Can only call functions and classes.".to_string(),
+                Some(paren.clone()),
+            ))
+        }
+    };
+
+    if values.len() != callable.arity() {
+        return Err(RuntimeError::new(
+            RuntimeErrorKind::ArityMismatch,
+            format!(
+                "Expected {} arguments but got {}.",
+                callable.arity(),
+                values.len()
+            ),
+            Some(paren.clone()),
+        ));
+    }
+    interpreter.enter_call(paren)?;
+    let result = callable.call(interpreter, values);
+    interpreter.exit_call();
+    result
+}
+
+fn get_property(object: Literal, name: &Token) -> Result<Literal, RuntimeError> {
+    match object {
+        Literal::Instance(instance) => LoxInstance::get(&instance, name),
+        _ => Err(RuntimeError::new(
+            RuntimeErrorKind::NotAnInstance,
+            "Only instances have properties.".to_string(),
+            Some(name.clone()),
+        )),
+    }
+}
+
+/// Evaluates the links of an optional chain from the innermost out, returning
+/// `None` as soon as a `?.` finds a nil receiver.
+fn optional_chain(
+    expr: &Expr,
+    interpreter: &mut Interpreter,
+) -> Result<Option<Literal>, RuntimeError> {
+    let (Expr::Call(object, ..)
+    | Expr::Get(object, _)
+    | Expr::OptionalGet(object, _)
+    | Expr::Index(object, ..)) = expr
+    else {
+        return expr.evaluate(interpreter).map(Some);
+    };
+    let Some(object) = optional_chain(object, interpreter)? else {
+        return Ok(None);
+    };
+    match expr {
+        Expr::Call(_, paren, arguments) => call(interpreter, object, paren, arguments).map(Some),
+        Expr::OptionalGet(_, _) if object == Literal::Nil => Ok(None),
+        Expr::Get(_, name) | Expr::OptionalGet(_, name) => get_property(object, name).map(Some),
+        Expr::Index(_, index, bracket) => {
+            let index = index.evaluate(interpreter)?;
+            get_index(&object, &index, bracket).map(Some)
+        }
+        _ => unreachable!("only chain links reach here"),
+    }
+}
+
+/// Applies an update's operator to the target's `old` value. `++` and `--`
+/// only work on numbers, while `+=` also concatenates strings.
+fn update(operator: &Token, old: Literal, value: Literal) -> Result<Literal, RuntimeError> {
+    if matches!(operator.kind, TokenKind::PlusPlus | TokenKind::MinusMinus) && !old.is_number() {
+        return Err(RuntimeError::new(
+            RuntimeErrorKind::InvalidOperand,
+            "Operand must be a number.".to_string(),
+            Some(operator.clone()),
+        ));
+    }
+    let kind = operator.kind.compound_operator().unwrap();
+    binary(kind, operator, old, value)
+}
+
+/// Reads `object[index]`, reporting errors at the closing `bracket`.
+fn get_index(object: &Literal, index: &Literal, bracket: &Token) -> Result<Literal, RuntimeError> {
+    match object {
+        Literal::List(list) => {
+            let list = list.borrow();
+            let i = list_index(list.len(), index, bracket)?;
+            Ok(list[i].clone())
+        }
+        Literal::Map(map) => {
+            let key = map_key(index, bracket)?;
+            map.borrow().get(&key).cloned().ok_or_else(|| {
+                let key = match index {
+                    Literal::String(s) => format!("{:?}", s),
+                    other => other.to_string(),
+                };
+                RuntimeError::new(
+                    RuntimeErrorKind::UndefinedKey,
+                    format!("Undefined key {}.", key),
+                    Some(bracket.clone()),
+                )
+            })
+        }
+        _ => Err(not_indexable(bracket)),
+    }
+}
+
+/// Stores `value` at `object[index]`, reporting errors at the closing
+/// `bracket`.
+fn set_index(
+    object: &Literal,
+    index: &Literal,
+    value: Literal,
+    bracket: &Token,
+) -> Result<(), RuntimeError> {
+    match object {
+        Literal::List(list) => {
+            let mut list = list.borrow_mut();
+            let i = list_index(list.len(), index, bracket)?;
+            list[i] = value;
+        }
+        Literal::Map(map) => {
+            let key = map_key(index, bracket)?;
+            map.borrow_mut().insert(key, value);
+        }
+        _ => return Err(not_indexable(bracket)),
+    }
+    Ok(())
+}
+
+fn not_indexable(bracket: &Token) -> RuntimeError {
+    RuntimeError::new(
+        RuntimeErrorKind::NotIndexable,
+        "Only lists and maps can be indexed.".to_string(),
+        Some(bracket.clone()),
+    )
+}
+
+/// Hashes `value` for use as a map key, reporting unhashable values at
+/// `token`.
+fn map_key(value: &Literal, token: &Token) -> Result<MapKey, RuntimeError> {
+    MapKey::new(value).map_err(|message| {
+        RuntimeError::new(
+            RuntimeErrorKind::UnhashableKey,
+            message.to_string(),
+            Some(token.clone()),
+        )
+    })
+}
+
+/// Checks that `index` is a whole number within a list of length `len`.
+fn list_index(len: usize, index: &Literal, bracket: &Token) -> Result<usize, RuntimeError> {
+    let Literal::Number(n) = *index else {
+        return Err(RuntimeError::new(
+            RuntimeErrorKind::InvalidIndex,
+            "List index must be a number.".to_string(),
+            Some(bracket.clone()),
+        ));
+    };
+    if n.fract() != 0.0 {
+        return Err(RuntimeError::new(
+            RuntimeErrorKind::InvalidIndex,
+            "List index must be an integer.".to_string(),
+            Some(bracket.clone()),
+        ));
+    }
+    if n < 0.0 || n >= len as f64 {
+        return Err(RuntimeError::new(
+            RuntimeErrorKind::IndexOutOfRange,
+            format!("List index {} out of range for length {}.", n, len),
+            Some(bracket.clone()),
+        ));
+    }
+    Ok(n as usize)
+}
+
+/// Applies the binary operator `kind` to two evaluated operands, reporting
+/// type errors at `operator`.
+fn binary(
+    kind: TokenKind,
+    operator: &Token,
+    left: Literal,
+    right: Literal,
+) -> Result<Literal, RuntimeError> {
+    match kind {
+        TokenKind::Plus => match (left, right) {
+            (Literal::Number(left), Literal::Number(right)) => Ok(Literal::Number(left + right)),
+            (Literal::String(left), Literal::String(right)) => {
+                Ok(Literal::String(format!("{}{}", left, right)))
+            }
+            (Literal::String(left), Literal::Number(right)) => {
+                Ok(Literal::String(format!("{}{}", left, right)))
+            }
+            (Literal::Number(left), Literal::String(right)) => {
+                Ok(Literal::String(format!("{}{}", left, right)))
+            }
+            _ => Err(RuntimeError::new(
+                RuntimeErrorKind::InvalidOperand,
+                "Operands must be numbers or strings".to_string(),
+                Some(operator.clone()),
+            )),
+        },
+        TokenKind::EqualEqual => Ok(Literal::Boolean(left == right)),
+        TokenKind::BangEqual => Ok(Literal::Boolean(left != right)),
+        _ => {
+            let (Literal::Number(left), Literal::Number(right)) = (left, right) else {
+                return Err(RuntimeError::new(
+                    RuntimeErrorKind::InvalidOperand,
+                    "Operands must be two numbers".to_string(),
+                    Some(operator.clone()),
+                ));
+            };
+            Ok(match kind {
+                TokenKind::Minus => Literal::Number(left - right),
+                TokenKind::Slash => Literal::Number(left / right),
+                TokenKind::Star => Literal::Number(left * right),
+                TokenKind::Percent => Literal::Number(left % right),
+                TokenKind::StarStar => Literal::Number(left.powf(right)),
+                TokenKind::Greater => Literal::Boolean(left > right),
+                TokenKind::GreaterEqual => Literal::Boolean(left >= right),
+                TokenKind::Less => Literal::Boolean(left < right),
+                TokenKind::LessEqual => Literal::Boolean(left <= right),
+                _ => panic!("Invalid binary operator"),
+            })
+        }
     }
 }
@@ -0,0 +1,118 @@
+use core::fmt;
+use std::{cell::RefCell, rc::Rc};
+
+use crate::{
+    class::LoxInstance,
+    environment::Environment,
+    interpreter::{outside_loop, Interpreter, RuntimeError, Unwind},
+    literal::Literal,
+    statement::Stmt,
+    token::Token,
+};
+
+pub trait Callable {
+    fn arity(&self) -> usize;
+
+    fn call(
+        &self,
+        interpreter: &mut Interpreter,
+        arguments: Vec<Literal>,
+    ) -> Result<Literal, RuntimeError>;
+}
+
+#[derive(Clone)]
+pub struct LoxFunction {
+    /// `None` for a lambda.
+    pub name: Option<Token>,
+    pub params: Vec<Token>,
+    pub body: Rc<Vec<Stmt>>,
+    pub closure: Rc<RefCell<Environment>>,
+    is_initializer: bool,
+}
+
+impl LoxFunction {
+    pub fn new(
+        name: Option<Token>,
+        params: Vec<Token>,
+        body: Rc<Vec<Stmt>>,
+        closure: Rc<RefCell<Environment>>,
+        is_initializer: bool,
+    ) -> Self {
+        LoxFunction {
+            name,
+            params,
+            body,
+            closure,
+            is_initializer,
+        }
+    }
+
+    /// Returns a copy of this method whose closure binds `this` to `instance`.
+    pub fn bind(&self, instance: Rc<RefCell<LoxInstance>>) -> LoxFunction {
+        let mut environment = Environment::new(Some(Rc::clone(&self.closure)));
+        environment.define("this".to_string(), Literal::Instance(instance));
+        LoxFunction::new(
+            self.name.clone(),
+            self.params.clone(),
+            Rc::clone(&self.body),
+            Rc::new(RefCell::new(environment)),
+            self.is_initializer,
+        )
+    }
+
+    fn this(&self) -> Literal {
+        self.closure
+            .borrow()
+            .get_at(0, "this")
+            .unwrap_or(Literal::Nil)
+    }
+}
+
+impl Callable for LoxFunction {
+    fn arity(&self) -> usize {
+        self.params.len()
+    }
+
+    fn call(
+        &self,
+        interpreter: &mut Interpreter,
+        arguments: Vec<Literal>,
+    ) -> Result<Literal, RuntimeError> {
+        let mut environment = Environment::new(Some(Rc::clone(&self.closure)));
+        for (param, argument) in self.params.iter().zip(arguments) {
+            environment.define(param.lexeme().to_string(), argument);
+        }
+
+        match interpreter.execute_block(&self.body, Rc::new(RefCell::new(environment))) {
+            Ok(()) | Err(Unwind::Return(_)) if self.is_initializer => Ok(self.this()),
+            Ok(()) => Ok(Literal::Nil),
+            Err(Unwind::Return(value)) => Ok(value),
+            Err(Unwind::Error(err)) => Err(err),
+            Err(Unwind::Break | Unwind::Continue) => Err(outside_loop()),
+        }
+    }
+}
+
+impl PartialEq for LoxFunction {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+impl fmt::Debug for LoxFunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.name {
+            Some(name) => write!(f, "LoxFunction({})", name.lexeme()),
+            None => write!(f, "LoxFunction(<anonymous>)"),
+        }
+    }
+}
+
+impl fmt::Display for LoxFunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.name {
+            Some(name) => write!(f, "<fn {}>", name.lexeme()),
+            None => write!(f, "<fn>"),
+        }
+    }
+}
@@ -1,119 +1,440 @@
+use std::cell::RefCell;
 use std::collections::HashMap;
+use std::rc::Rc;
 
 use crate::{
+    class::LoxClass,
     environment::Environment,
-    expression::{Evaluate, Expr},
+    expression::{Evaluate, Expr, ExprId},
+    function::LoxFunction,
+    literal::Literal,
     statement::Stmt,
     token::Token,
 };
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RuntimeErrorKind {
+    UndefinedVariable,
+    UndefinedProperty,
+    InvalidOperand,
+    NotAnInstance,
+    NotCallable,
+    ArityMismatch,
+    SuperclassNotClass,
+    StackOverflow,
+    NotIndexable,
+    InvalidIndex,
+    IndexOutOfRange,
+    UnhashableKey,
+    UndefinedKey,
+    /// `return`, `break`, `continue` or `super` where the resolver and parser
+    /// should already have rejected it.
+    InvalidContext,
+    /// An operator the evaluator doesn't know, which the parser never produces.
+    Internal,
+}
+
+impl RuntimeErrorKind {
+    pub fn code(&self) -> &'static str {
+        match self {
+            RuntimeErrorKind::UndefinedVariable => "undefined-variable",
+            RuntimeErrorKind::UndefinedProperty => "undefined-property",
+            RuntimeErrorKind::InvalidOperand => "invalid-operand",
+            RuntimeErrorKind::NotAnInstance => "not-an-instance",
+            RuntimeErrorKind::NotCallable => "not-callable",
+            RuntimeErrorKind::ArityMismatch => "arity-mismatch",
+            RuntimeErrorKind::SuperclassNotClass => "superclass-not-class",
+            RuntimeErrorKind::StackOverflow => "stack-overflow",
+            RuntimeErrorKind::NotIndexable => "not-indexable",
+            RuntimeErrorKind::InvalidIndex => "invalid-index",
+            RuntimeErrorKind::IndexOutOfRange => "index-out-of-range",
+            RuntimeErrorKind::UnhashableKey => "unhashable-key",
+            RuntimeErrorKind::UndefinedKey => "undefined-key",
+            RuntimeErrorKind::InvalidContext => "invalid-context",
+            RuntimeErrorKind::Internal => "internal-error",
+        }
+    }
+}
+
+#[derive(Debug)]
 pub struct RuntimeError {
+    pub kind: RuntimeErrorKind,
     pub message: String,
     pub token: Option<Token>,
 }
 
 impl RuntimeError {
-    pub fn new(message: String, token: Option<Token>) -> Self {
-        RuntimeError { message, token }
+    pub fn new(kind: RuntimeErrorKind, message: String, token: Option<Token>) -> Self {
+        RuntimeError {
+            kind,
+            message,
+            token,
+        }
     }
+}
 
-    pub fn print(&self) {
-        if self.token.is_none() {
-            eprintln!("{}", self.message);
-            return;
-        }
-        eprintln!(
-            "{} \n[Line {} ]",
-            self.message,
-            self.token.clone().unwrap().line
-        );
+/// Reasons for a statement to stop executing before reaching its end.
+#[derive(Debug)]
+pub enum Unwind {
+    Error(RuntimeError),
+    Return(Literal),
+    Break,
+    Continue,
+}
+
+impl From<RuntimeError> for Unwind {
+    fn from(err: RuntimeError) -> Self {
+        Unwind::Error(err)
     }
 }
+
+/// How many calls may be in progress at once before a call fails with a
+/// stack overflow error.
+pub const MAX_CALL_DEPTH: usize = 1000;
+
+/// How much Rust stack nested calls may use, measured from where the
+/// outermost one started. Spawned Rust threads get 2 MiB by default, so this
+/// leaves room for whatever runs the interpreter and for the frames of the
+/// call that hits the limit.
+const STACK_BUDGET: usize = 1024 * 1024;
+
 pub struct Interpreter {
-    pub globals: Environment,
-    environment: Environment,
-    locals: HashMap<Expr, usize>,
+    pub globals: Rc<RefCell<Environment>>,
+    pub(crate) environment: Rc<RefCell<Environment>>,
+    locals: HashMap<ExprId, usize>,
+    call_depth: usize,
+    /// Stack address at which the outermost call in progress started.
+    stack_base: usize,
+}
+
+impl Default for Interpreter {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Interpreter {
     pub fn new() -> Self {
-        let globals = Environment::new(None);
-        let environment = globals.clone();
+        let globals = Rc::new(RefCell::new(Environment::new(None)));
+        let environment = Rc::clone(&globals);
         let locals = HashMap::new();
         Interpreter {
             globals,
             environment,
             locals,
+            call_depth: 0,
+            stack_base: 0,
         }
     }
 
     pub fn interpret(&mut self, statements: Vec<Stmt>) -> Result<(), RuntimeError> {
-        for statement in statements {
-            if let Err(err) = self.execute(&statement) {
-                return Err(err);
+        for statement in &statements {
+            match self.execute(statement) {
+                Ok(()) => {}
+                Err(Unwind::Error(err)) => return Err(err),
+                Err(Unwind::Return(_)) => {
+                    return Err(RuntimeError::new(
+                        RuntimeErrorKind::InvalidContext,
+                        "Can't return from top-level code.".to_string(),
+                        None,
+                    ))
+                }
+                Err(Unwind::Break | Unwind::Continue) => return Err(outside_loop()),
             }
         }
         Ok(())
     }
 
-    fn execute(&mut self, statement: &Stmt) -> Result<(), RuntimeError> {
+    pub fn evaluate(&mut self, expr: &Expr) -> Result<Literal, RuntimeError> {
+        expr.evaluate(self)
+    }
+
+    /// Records that the variable expression `id` lives `depth` scopes out
+    /// from where it is used. Unresolved variables are looked up as globals.
+    pub fn resolve(&mut self, id: ExprId, depth: usize) {
+        self.locals.insert(id, depth);
+    }
+
+    pub fn look_up_variable(&self, name: &Token, id: ExprId) -> Result<Literal, RuntimeError> {
+        match self.locals.get(&id) {
+            Some(&distance) => self
+                .environment
+                .borrow()
+                .get_at(distance, name.lexeme())
+                .ok_or_else(|| {
+                    RuntimeError::new(
+                        RuntimeErrorKind::UndefinedVariable,
+                        format!("Undefined variable '{}'.", name.lexeme()),
+                        Some(name.clone()),
+                    )
+                }),
+            None => self.globals.borrow().get(name.clone()),
+        }
+    }
+
+    pub fn assign_variable(
+        &mut self,
+        name: &Token,
+        id: ExprId,
+        value: Literal,
+    ) -> Result<(), RuntimeError> {
+        match self.locals.get(&id) {
+            Some(&distance) => {
+                self.environment
+                    .borrow_mut()
+                    .assign_at(distance, name.clone(), value);
+                Ok(())
+            }
+            None => self.globals.borrow_mut().assign(name.clone(), value),
+        }
+    }
+
+    pub fn look_up_super(
+        &self,
+        keyword: &Token,
+        method: &Token,
+        id: ExprId,
+    ) -> Result<Literal, RuntimeError> {
+        let distance = self.locals.get(&id).copied().unwrap_or(0);
+        let environment = self.environment.borrow();
+        let superclass = environment.get_at(distance, "super");
+        // `this` is always bound in the scope just inside the one holding `super`.
+        let object = environment.get_at(distance.saturating_sub(1), "this");
+
+        match (superclass, object) {
+            (Some(Literal::Class(superclass)), Some(Literal::Instance(instance))) => {
+                match superclass.find_method(method.lexeme()) {
+                    Some(found) => Ok(Literal::Function(Rc::new(found.bind(instance)))),
+                    None => Err(RuntimeError::new(
+                        RuntimeErrorKind::UndefinedProperty,
+                        format!("Undefined property '{}'.", method.lexeme()),
+                        Some(method.clone()),
+                    )),
+                }
+            }
+            _ => Err(RuntimeError::new(
+                RuntimeErrorKind::InvalidContext,
+                "Can't use 'super' outside of a subclass method.".to_string(),
+                Some(keyword.clone()),
+            )),
+        }
+    }
+
+    /// Records the start of a call, failing with a stack overflow error at
+    /// `paren` when calls are nested too deeply. Each successful call must be
+    /// matched by `exit_call`.
+    pub(crate) fn enter_call(&mut self, paren: &Token) -> Result<(), RuntimeError> {
+        let marker = 0u8;
+        let here = std::hint::black_box(&marker) as *const u8 as usize;
+        if self.call_depth == 0 {
+            self.stack_base = here;
+        }
+        // The stack grows down on every platform Rust supports well; where it
+        // doesn't, only the depth limit applies.
+        if self.call_depth == MAX_CALL_DEPTH || self.stack_base.saturating_sub(here) > STACK_BUDGET
+        {
+            return Err(RuntimeError::new(
+                RuntimeErrorKind::StackOverflow,
+                "Stack overflow.".to_string(),
+                Some(paren.clone()),
+            ));
+        }
+        self.call_depth += 1;
+        Ok(())
+    }
+
+    pub(crate) fn exit_call(&mut self) {
+        self.call_depth -= 1;
+    }
+
+    /// Runs `statements` in `environment`, restoring the current environment
+    /// afterwards even if execution unwinds early.
+    pub fn execute_block(
+        &mut self,
+        statements: &[Stmt],
+        environment: Rc<RefCell<Environment>>,
+    ) -> Result<(), Unwind> {
+        let previous = std::mem::replace(&mut self.environment, environment);
+        let result = statements
+            .iter()
+            .try_for_each(|statement| self.execute(statement));
+        self.environment = previous;
+        result
+    }
+
+    // Like `Expr::evaluate`, this frame is on the path of every Lox call, so
+    // statements with locals of their own are run by separate methods.
+    fn execute(&mut self, statement: &Stmt) -> Result<(), Unwind> {
         match statement {
-            Stmt::Expression(expr) => {
-                expr.evaluate(&mut self.environment)?;
+            Stmt::Expression(expr, _) => self.execute_expression(expr),
+            Stmt::Print(expr, _) => self.execute_print(expr),
+            Stmt::Var(name, expr, _) => self.execute_var(name, expr.as_ref()),
+            Stmt::Block(statements, _) => {
+                let environment = Environment::new(Some(Rc::clone(&self.environment)));
+                self.execute_block(statements, Rc::new(RefCell::new(environment)))
             }
-            Stmt::Print(expr) => {
-                let value = expr.evaluate(&mut self.environment)?;
-                println!("{}", value);
+            Stmt::If(condition, then_branch, else_branch, _) => {
+                self.execute_if(condition, then_branch, else_branch.as_deref())
             }
-            Stmt::Var(name, expr) => match expr {
-                Some(expr) => {
-                    let value = expr.evaluate(&mut self.environment)?;
-                    self.environment.define(name.lexeme.clone(), value);
-                }
-                None => {
-                    self.environment
-                        .define(name.lexeme.clone(), crate::literal::Literal::Nil);
-                }
-            },
-            Stmt::Block(statements) => {
-                self.environment = Environment::new(Some(Box::new(self.environment.clone())));
-                self.interpret(statements.to_vec())?;
-                self.environment = *self.environment.enclosing.clone().unwrap();
+            Stmt::While(condition, body, increment, _) => {
+                self.execute_while(condition, body, increment.as_ref())
             }
-            Stmt::If(condition, then_branch, else_branch) => {
-                if condition.evaluate(&mut self.environment)?.is_truthy() {
-                    self.execute(then_branch)?;
-                } else if let Some(else_branch) = else_branch {
-                    self.execute(else_branch)?;
-                }
+            Stmt::Break(..) => Err(Unwind::Break),
+            Stmt::Continue(..) => Err(Unwind::Continue),
+            Stmt::Function(name, params, body, _) => {
+                self.execute_function(name, params, body);
+                Ok(())
             }
-            Stmt::While(condition, body) => {
-                while condition.evaluate(&mut self.environment)?.is_truthy() {
-                    self.execute(body)?;
-                }
+            Stmt::Return(_, value, _) => self.execute_return(value.as_ref()),
+            Stmt::Class(name, superclass, methods, _) => {
+                Ok(self.execute_class(name, superclass.as_ref(), methods)?)
             }
-            // Stmt::Function(name, params, body) => {
-            //     let function = crate::literal::Literal::Function(
-            //         name.lexeme.clone(),
-            //         params.clone(),
-            //         body.clone(),
-            //         self.environment.clone(),
-            //     );
-            //     self.environment.define(name.lexeme.clone(), function);
-            // }
-            // Stmt::Return(keyword, value) => {
-            //     let value = if let Some(value) = value {
-            //         self.evaluate(value)?
-            //     } else {
-            //         crate::literal::Literal::Nil
-            //     };
-            //     return Err(RuntimeError::new(
-            //         "Return".to_string(),
-            //         Some(keyword.clone()),
-            //     ));
-            // }
-            _ => unimplemented!(),
         }
+    }
+
+    fn execute_expression(&mut self, expr: &Expr) -> Result<(), Unwind> {
+        expr.evaluate(self)?;
+        Ok(())
+    }
+
+    fn execute_print(&mut self, expr: &Expr) -> Result<(), Unwind> {
+        let value = expr.evaluate(self)?;
+        println!("{}", value);
+        Ok(())
+    }
+
+    fn execute_if(
+        &mut self,
+        condition: &Expr,
+        then_branch: &Stmt,
+        else_branch: Option<&Stmt>,
+    ) -> Result<(), Unwind> {
+        if condition.evaluate(self)?.is_truthy() {
+            self.execute(then_branch)
+        } else if let Some(else_branch) = else_branch {
+            self.execute(else_branch)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn execute_return(&mut self, value: Option<&Expr>) -> Result<(), Unwind> {
+        let value = match value {
+            Some(value) => value.evaluate(self)?,
+            None => Literal::Nil,
+        };
+        Err(Unwind::Return(value))
+    }
+
+    fn execute_var(&mut self, name: &Token, expr: Option<&Expr>) -> Result<(), Unwind> {
+        let value = match expr {
+            Some(expr) => expr.evaluate(self)?,
+            None => Literal::Nil,
+        };
+        self.environment
+            .borrow_mut()
+            .define(name.lexeme().to_string(), value);
         Ok(())
     }
+
+    fn execute_while(
+        &mut self,
+        condition: &Expr,
+        body: &Stmt,
+        increment: Option<&Expr>,
+    ) -> Result<(), Unwind> {
+        while condition.evaluate(self)?.is_truthy() {
+            match self.execute(body) {
+                Ok(()) | Err(Unwind::Continue) => {}
+                Err(Unwind::Break) => break,
+                Err(unwind) => return Err(unwind),
+            }
+            if let Some(increment) = increment {
+                increment.evaluate(self)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn execute_function(&mut self, name: &Token, params: &[Token], body: &Rc<Vec<Stmt>>) {
+        let function = LoxFunction::new(
+            Some(name.clone()),
+            params.to_vec(),
+            Rc::clone(body),
+            Rc::clone(&self.environment),
+            false,
+        );
+        self.environment.borrow_mut().define(
+            name.lexeme().to_string(),
+            Literal::Function(Rc::new(function)),
+        );
+    }
+
+    fn execute_class(
+        &mut self,
+        name: &Token,
+        superclass: Option<&Expr>,
+        methods: &[Stmt],
+    ) -> Result<(), RuntimeError> {
+        let superclass = match superclass {
+            Some(expr) => match expr.evaluate(self)? {
+                Literal::Class(class) => Some(class),
+                _ => {
+                    let token = match expr {
+                        Expr::Variable(token, _) => Some(token.clone()),
+                        _ => Some(name.clone()),
+                    };
+                    return Err(RuntimeError::new(
+                        RuntimeErrorKind::SuperclassNotClass,
+                        "Superclass must be a class.".to_string(),
+                        token,
+                    ));
+                }
+            },
+            None => None,
+        };
+
+        self.environment
+            .borrow_mut()
+            .define(name.lexeme().to_string(), Literal::Nil);
+
+        // Methods of a subclass close over an extra scope that binds `super`.
+        let closure = match &superclass {
+            Some(superclass) => {
+                let mut environment = Environment::new(Some(Rc::clone(&self.environment)));
+                environment.define("super".to_string(), Literal::Class(Rc::clone(superclass)));
+                Rc::new(RefCell::new(environment))
+            }
+            None => Rc::clone(&self.environment),
+        };
+
+        let mut functions = HashMap::new();
+        for method in methods {
+            if let Stmt::Function(method_name, params, body, _) = method {
+                let function = LoxFunction::new(
+                    Some(method_name.clone()),
+                    params.clone(),
+                    Rc::clone(body),
+                    Rc::clone(&closure),
+                    method_name.lexeme() == "init",
+                );
+                functions.insert(method_name.lexeme().to_string(), Rc::new(function));
+            }
+        }
+
+        let class = LoxClass::new(name.lexeme().to_string(), superclass, functions);
+        self.environment
+            .borrow_mut()
+            .assign(name.clone(), Literal::Class(Rc::new(class)))
+    }
+}
+
+/// The parser rejects `break` and `continue` outside loops, so this only
+/// guards against them escaping a function or the program.
+pub(crate) fn outside_loop() -> RuntimeError {
+    RuntimeError::new(
+        RuntimeErrorKind::InvalidContext,
+        "Can't use 'break' or 'continue' outside of a loop.".to_string(),
+        None,
+    )
 }
@@ -1,24 +1,41 @@
+pub mod class;
+pub mod diagnostic;
 pub mod environment;
 pub mod expression;
+pub mod function;
 pub mod interpreter;
 pub mod literal;
+pub mod map;
 pub mod parser;
+pub mod repl;
+pub mod resolver;
 pub mod scanner;
+pub mod span;
 pub mod statement;
 pub mod token;
 pub mod token_kind;
 
 use std::fs;
-use std::io::{self, Write};
+use std::io::{self, IsTerminal};
 use std::process::exit;
+use std::rc::Rc;
 
-use interpreter::Interpreter;
+use diagnostic::{Diagnostic, ErrorFormat};
+use interpreter::{Interpreter, RuntimeError};
+use literal::Literal;
 use parser::Parser;
+use repl::Repl;
+use resolver::Resolver;
 use scanner::Scanner;
+use statement::Stmt;
 
-pub fn run_file(file_path: &str) {
+pub fn run_file(file_path: &str, format: ErrorFormat) {
     match fs::read_to_string(file_path) {
-        Ok(source) => run(source),
+        Ok(source) => {
+            if let Err(code) = run(&mut Interpreter::new(), file_path, &source, format) {
+                exit(code)
+            }
+        }
         Err(err) => {
             eprintln!("Error reading file {}: {}", file_path, err);
             exit(1);
@@ -26,29 +43,126 @@ pub fn run_file(file_path: &str) {
     }
 }
 
-pub fn run_prompt() {
-    loop {
-        print!("> ");
-        io::stdout().flush().unwrap();
+pub fn run_prompt(format: ErrorFormat) {
+    // A single interpreter lives for the whole session so definitions from
+    // earlier lines stay visible, and errors are reported without exiting.
+    Repl::new(format).run();
+}
 
-        let mut line = String::new();
-        io::stdin()
-            .read_line(&mut line)
-            .expect("Failed to read line");
+/// Runs `source`, read from `file_name`, in `interpreter`, reporting any
+/// errors to stderr in the given format.
+///
+/// On failure returns the exit code for the error: 65 for static errors and
+/// 70 for runtime errors.
+pub fn run(
+    interpreter: &mut Interpreter,
+    file_name: &str,
+    source: &str,
+    format: ErrorFormat,
+) -> Result<(), i32> {
+    let reporter = Reporter {
+        file_name,
+        source,
+        format,
+    };
+    let statements = parse(&reporter, false)?;
+    Resolver::new(interpreter)
+        .resolve(&statements)
+        .map_err(|errors| reporter.static_errors(errors))?;
 
-        let line = line.trim();
-        if line.is_empty() {
-            break;
-        }
-        run(line.to_string());
+    interpreter
+        .interpret(statements)
+        .map_err(|err| reporter.runtime_error(err))
+}
+
+/// Like [`run`], but for a line typed at the REPL: a trailing expression may
+/// omit its semicolon, and its value is returned so it can be echoed.
+pub fn run_repl(
+    interpreter: &mut Interpreter,
+    source: &str,
+    format: ErrorFormat,
+) -> Result<Option<Literal>, i32> {
+    let reporter = Reporter {
+        file_name: "<repl>",
+        source,
+        format,
+    };
+    let mut statements = parse(&reporter, true)?;
+    Resolver::new(interpreter)
+        .resolve(&statements)
+        .map_err(|errors| reporter.static_errors(errors))?;
+
+    let expr = match statements.pop_if(|stmt| matches!(stmt, Stmt::Expression(..))) {
+        Some(Stmt::Expression(expr, _)) => Some(expr),
+        _ => None,
+    };
+
+    interpreter
+        .interpret(statements)
+        .map_err(|err| reporter.runtime_error(err))?;
+    match expr {
+        Some(expr) => interpreter
+            .evaluate(&expr)
+            .map(Some)
+            .map_err(|err| reporter.runtime_error(err)),
+        None => Ok(None),
+    }
+}
+
+/// Scans and parses the reporter's source in a single streaming pass.
+/// Lexical errors are reported instead of any parse errors, which they
+/// usually cause.
+fn parse(reporter: &Reporter, repl: bool) -> Result<Vec<Stmt>, i32> {
+    let mut scanner = Scanner::new(reporter.source);
+    let mut parser = Parser::new(&mut scanner);
+    let statements = if repl {
+        parser.parse_repl()
+    } else {
+        parser.parse()
+    };
+
+    if !scanner.errors().is_empty() {
+        return Err(reporter.static_errors(scanner.errors().to_vec()));
     }
+    statements.map_err(|errors| reporter.static_errors(errors))
 }
 
-fn run(source: String) {
-    let tokens = Scanner::new(&source).scan_tokens();
-    let statements = Parser::new(tokens).parse();
-    if let Err(err) = Interpreter::new().interpret(statements) {
-        err.print();
-        exit(70)
+/// Writes diagnostics for one source to stderr.
+struct Reporter<'a> {
+    file_name: &'a str,
+    source: &'a str,
+    format: ErrorFormat,
+}
+
+impl Reporter<'_> {
+    /// Reports errors found before the program runs and returns their exit
+    /// code.
+    fn static_errors<E: Into<Diagnostic>>(&self, errors: Vec<E>) -> i32 {
+        for err in errors {
+            self.report(err.into());
+        }
+        65
+    }
+
+    /// Runtime errors are rendered against the source of their token, which
+    /// in the REPL may be an earlier line than the one being run.
+    fn runtime_error(&self, err: RuntimeError) -> i32 {
+        let source = err.token.as_ref().map(|token| Rc::clone(&token.source));
+        self.report_in(err.into(), source.as_deref().unwrap_or(self.source));
+        70
+    }
+
+    fn report(&self, diagnostic: Diagnostic) {
+        self.report_in(diagnostic, self.source);
+    }
+
+    fn report_in(&self, diagnostic: Diagnostic, source: &str) {
+        match self.format {
+            ErrorFormat::Human => {
+                let color = io::stderr().is_terminal();
+                eprint!("{}", diagnostic.render(self.file_name, source, color));
+            }
+            ErrorFormat::Json => eprintln!("{}", diagnostic.to_json(self.file_name)),
+        }
     }
 }
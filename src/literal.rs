@@ -1,23 +1,28 @@
 use core::fmt;
+use std::{cell::RefCell, rc::Rc};
 
-#[derive(Debug, Clone, PartialEq)]
+use crate::{
+    class::{LoxClass, LoxInstance},
+    function::LoxFunction,
+    map::LoxMap,
+};
+
+#[derive(Debug, Clone)]
 pub enum Literal {
     Number(f64),
     String(String),
     Boolean(bool),
+    Function(Rc<LoxFunction>),
+    Class(Rc<LoxClass>),
+    Instance(Rc<RefCell<LoxInstance>>),
+    /// Lists are shared: copies of the value refer to the same list.
+    List(Rc<RefCell<Vec<Literal>>>),
+    /// Maps are shared the same way lists are.
+    Map(Rc<RefCell<LoxMap>>),
     Nil,
 }
 
 impl Literal {
-    pub fn to_string(&self) -> String {
-        match self {
-            Literal::Number(n) => n.to_string(),
-            Literal::String(s) => s.to_string(),
-            Literal::Boolean(b) => b.to_string(),
-            Literal::Nil => "nil".into(),
-        }
-    }
-
     pub fn is_truthy(&self) -> bool {
         match self {
             Literal::Boolean(b) => *b,
@@ -27,8 +32,82 @@ impl Literal {
     }
 
     pub fn is_number(&self) -> bool {
+        matches!(self, Literal::Number(_))
+    }
+
+    /// Writes the value as `print` shows it. `seen` holds the lists and maps
+    /// being written further out, so a list containing itself prints as
+    /// `[...]` and a map containing itself as `{...}`.
+    fn write(&self, f: &mut fmt::Formatter<'_>, seen: &mut Vec<*const ()>) -> fmt::Result {
         match self {
-            Literal::Number(_) => true,
+            Literal::Number(n) => write!(f, "{}", n),
+            Literal::String(s) => write!(f, "{}", s),
+            Literal::Boolean(b) => write!(f, "{}", b),
+            Literal::Function(function) => write!(f, "{}", function),
+            Literal::Class(class) => write!(f, "{}", class),
+            Literal::Instance(instance) => write!(f, "{}", instance.borrow()),
+            Literal::List(list) => {
+                let ptr = Rc::as_ptr(list) as *const ();
+                if seen.contains(&ptr) {
+                    return write!(f, "[...]");
+                }
+                seen.push(ptr);
+                write!(f, "[")?;
+                for (i, item) in list.borrow().iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    item.write_nested(f, seen)?;
+                }
+                seen.pop();
+                write!(f, "]")
+            }
+            Literal::Map(map) => {
+                let ptr = Rc::as_ptr(map) as *const ();
+                if seen.contains(&ptr) {
+                    return write!(f, "{{...}}");
+                }
+                seen.push(ptr);
+                write!(f, "{{")?;
+                for (i, (key, value)) in map.borrow().iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    key.to_literal().write_nested(f, seen)?;
+                    write!(f, ": ")?;
+                    value.write_nested(f, seen)?;
+                }
+                seen.pop();
+                write!(f, "}}")
+            }
+            Literal::Nil => write!(f, "nil"),
+        }
+    }
+
+    /// Like `write`, but quotes strings so they stand out inside a list or
+    /// map.
+    fn write_nested(&self, f: &mut fmt::Formatter<'_>, seen: &mut Vec<*const ()>) -> fmt::Result {
+        match self {
+            Literal::String(s) => write!(f, "{:?}", s),
+            _ => self.write(f, seen),
+        }
+    }
+}
+
+/// Lists and maps, like functions, classes and instances, are equal only to
+/// themselves.
+impl PartialEq for Literal {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Literal::Number(a), Literal::Number(b)) => a == b,
+            (Literal::String(a), Literal::String(b)) => a == b,
+            (Literal::Boolean(a), Literal::Boolean(b)) => a == b,
+            (Literal::Function(a), Literal::Function(b)) => a == b,
+            (Literal::Class(a), Literal::Class(b)) => a == b,
+            (Literal::Instance(a), Literal::Instance(b)) => a == b,
+            (Literal::List(a), Literal::List(b)) => Rc::ptr_eq(a, b),
+            (Literal::Map(a), Literal::Map(b)) => Rc::ptr_eq(a, b),
+            (Literal::Nil, Literal::Nil) => true,
             _ => false,
         }
     }
@@ -36,6 +115,6 @@ impl Literal {
 
 impl fmt::Display for Literal {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.to_string())
+        self.write(f, &mut Vec::new())
     }
 }
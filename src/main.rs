@@ -1,17 +1,31 @@
+use rslox::diagnostic::ErrorFormat;
 use rslox::{run_file, run_prompt};
 use std::{env, process::exit};
 
+const USAGE: &str = "Usage: rslox [--error-format=human|json] [script]";
+
 fn main() {
-    let args: Vec<String> = env::args().collect();
-    if args.len() > 2 {
-        print!("Usage: rslox [script]");
-        exit(64)
-    }
-    if args.len() == 1 {
-        run_prompt();
-        return;
+    let mut format = ErrorFormat::Human;
+    let mut scripts = Vec::new();
+    for arg in env::args().skip(1) {
+        match arg.strip_prefix("--error-format=") {
+            Some(name) => match ErrorFormat::from_name(name) {
+                Some(chosen) => format = chosen,
+                None => {
+                    eprintln!("Unknown error format '{}'.\n{}", name, USAGE);
+                    exit(64)
+                }
+            },
+            None => scripts.push(arg),
+        }
     }
 
-    let file_path = &args[1];
-    run_file(file_path);
+    match scripts.as_slice() {
+        [] => run_prompt(format),
+        [file_path] => run_file(file_path, format),
+        _ => {
+            println!("{}", USAGE);
+            exit(64)
+        }
+    }
 }
@@ -0,0 +1,77 @@
+use std::collections::HashMap;
+
+use crate::literal::Literal;
+
+/// A map value's key. Only numbers, strings, booleans and nil can be hashed;
+/// numbers are compared by their bits, so `-0` is stored as `0` and NaN,
+/// which is not equal to itself, is rejected.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum MapKey {
+    Number(u64),
+    String(String),
+    Boolean(bool),
+    Nil,
+}
+
+impl MapKey {
+    /// Returns the key for `value`, or the reason it can't be used as one.
+    pub fn new(value: &Literal) -> Result<Self, &'static str> {
+        match value {
+            Literal::Number(n) if n.is_nan() => Err("NaN can't be used as a map key."),
+            Literal::Number(n) => Ok(MapKey::Number(if *n == 0.0 { 0.0 } else { *n }.to_bits())),
+            Literal::String(s) => Ok(MapKey::String(s.clone())),
+            Literal::Boolean(b) => Ok(MapKey::Boolean(*b)),
+            Literal::Nil => Ok(MapKey::Nil),
+            _ => Err("Only numbers, strings, booleans and nil can be map keys."),
+        }
+    }
+
+    pub fn to_literal(&self) -> Literal {
+        match self {
+            MapKey::Number(bits) => Literal::Number(f64::from_bits(*bits)),
+            MapKey::String(s) => Literal::String(s.clone()),
+            MapKey::Boolean(b) => Literal::Boolean(*b),
+            MapKey::Nil => Literal::Nil,
+        }
+    }
+}
+
+/// Entries are kept in insertion order so maps print predictably.
+#[derive(Debug, Default)]
+pub struct LoxMap {
+    entries: Vec<(MapKey, Literal)>,
+    index: HashMap<MapKey, usize>,
+}
+
+impl LoxMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, key: &MapKey) -> Option<&Literal> {
+        self.index.get(key).map(|&i| &self.entries[i].1)
+    }
+
+    /// Stores `value` under `key`, replacing any earlier value in place.
+    pub fn insert(&mut self, key: MapKey, value: Literal) {
+        match self.index.get(&key) {
+            Some(&i) => self.entries[i].1 = value,
+            None => {
+                self.index.insert(key.clone(), self.entries.len());
+                self.entries.push((key, value));
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &(MapKey, Literal)> {
+        self.entries.iter()
+    }
+}
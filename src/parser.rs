@@ -1,64 +1,176 @@
-use std::vec::Vec;
+use core::fmt;
+use std::{mem, rc::Rc};
 
 use crate::{
-    expression::Expr, literal::Literal, statement::Stmt, token::Token, token_kind::TokenKind,
+    expression::{next_expr_id, Expr},
+    literal::Literal,
+    span::Span,
+    statement::Stmt,
+    token::Token,
+    token_kind::TokenKind,
 };
 
-#[derive(Debug)]
-pub struct ParseError {
-    message: String,
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ParseErrorKind {
+    ExpectedToken(TokenKind),
+    ExpectedExpression,
+    InvalidAssignmentTarget,
+    TooManyParameters,
+    TooManyArguments,
+    OutsideLoop,
+}
+
+impl ParseErrorKind {
+    pub fn code(&self) -> &'static str {
+        match self {
+            ParseErrorKind::ExpectedToken(_) => "expected-token",
+            ParseErrorKind::ExpectedExpression => "expected-expression",
+            ParseErrorKind::InvalidAssignmentTarget => "invalid-assignment-target",
+            ParseErrorKind::TooManyParameters => "too-many-parameters",
+            ParseErrorKind::TooManyArguments => "too-many-arguments",
+            ParseErrorKind::OutsideLoop => "outside-loop",
+        }
+    }
 }
 
-pub struct Parser {
-    tokens: Vec<Token>,
-    current: usize,
+#[derive(Debug, Clone)]
+pub struct ParseError {
+    pub token: Token,
+    pub kind: ParseErrorKind,
+    pub message: String,
 }
 
-impl Parser {
-    pub fn new(tokens: Vec<Token>) -> Self {
-        Self { tokens, current: 0 }
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.token.kind == TokenKind::Eof {
+            write!(
+                f,
+                "[line {}] Error at end: {}",
+                self.token.line, self.message
+            )
+        } else {
+            write!(
+                f,
+                "[line {}] Error at '{}': {}",
+                self.token.line,
+                self.token.lexeme(),
+                self.message
+            )
+        }
     }
+}
+
+/// Parses a stream of tokens, such as a `Vec<Token>` or a
+/// [`Scanner`](crate::scanner::Scanner), pulling them in only as needed. The
+/// stream must end with an `Eof` token.
+pub struct Parser<I: Iterator<Item = Token>> {
+    tokens: I,
+    /// The next token, not yet consumed.
+    current: Token,
+    /// The token after `current`, once something has looked that far ahead.
+    lookahead: Option<Token>,
+    previous: Option<Token>,
+    repl: bool,
+    /// How many `if`, `while` and `for` statements enclose the code being
+    /// parsed.
+    nesting: usize,
+    /// How many loops enclose the code being parsed, within the current
+    /// function.
+    loop_depth: usize,
+    errors: Vec<ParseError>,
+}
 
-    pub fn parse(&mut self) -> Vec<Stmt> {
+impl<I: Iterator<Item = Token>> Parser<I> {
+    pub fn new(tokens: impl IntoIterator<IntoIter = I>) -> Self {
+        let mut tokens = tokens.into_iter();
+        let current = tokens
+            .next()
+            .expect("token stream must end with an Eof token");
+        Self {
+            tokens,
+            current,
+            lookahead: None,
+            previous: None,
+            repl: false,
+            nesting: 0,
+            loop_depth: 0,
+            errors: Vec::new(),
+        }
+    }
+
+    /// Parses REPL input, where a trailing top-level expression may omit its
+    /// semicolon.
+    pub fn parse_repl(&mut self) -> Result<Vec<Stmt>, Vec<ParseError>> {
+        self.repl = true;
+        self.parse()
+    }
+
+    /// Parses the whole program, recovering after each syntax error so that
+    /// every error is reported at once.
+    pub fn parse(&mut self) -> Result<Vec<Stmt>, Vec<ParseError>> {
         let mut statements = Vec::new();
         while !self.is_at_end() {
             if let Some(stmt) = self.declaration() {
                 statements.push(stmt);
             }
         }
-        statements
+
+        if self.errors.is_empty() {
+            Ok(statements)
+        } else {
+            Err(mem::take(&mut self.errors))
+        }
     }
+
     fn declaration(&mut self) -> Option<Stmt> {
-        if self.match_token(&[TokenKind::Fun]) {
-            match self.function("function") {
-                Ok(stmt) => return Some(stmt),
-                Err(e) => {
-                    eprintln!("{}", e.message);
-                    self.synchronize();
-                    return None;
-                }
+        let result = if self.match_token(&[TokenKind::Class]) {
+            self.class_declaration()
+        } else if self.check(TokenKind::Fun) && self.check_next(TokenKind::Identifier) {
+            // A `fun` not followed by a name starts a lambda expression.
+            self.advance();
+            self.function("function", self.previous().span())
+        } else if self.match_token(&[TokenKind::Var]) {
+            self.var_declaration()
+        } else {
+            self.statement()
+        };
+
+        match result {
+            Ok(stmt) => Some(stmt),
+            Err(err) => {
+                self.errors.push(err);
+                self.synchronize();
+                None
             }
         }
+    }
 
-        if self.match_token(&[TokenKind::Var]) {
-            match self.var_declaration() {
-                Ok(stmt) => return Some(stmt),
-                Err(e) => {
-                    eprintln!("{}", e.message);
-                    self.synchronize();
-                    return None;
-                }
-            }
+    fn class_declaration(&mut self) -> Result<Stmt, ParseError> {
+        let start = self.previous().span();
+        let name = self.consume(TokenKind::Identifier, "Expect class name.")?;
+
+        let mut superclass: Option<Expr> = None;
+        if self.match_token(&[TokenKind::Less]) {
+            let superclass_name = self.consume(TokenKind::Identifier, "Expect superclass name.")?;
+            superclass = Some(Expr::Variable(superclass_name, next_expr_id()));
         }
 
-        match self.statement() {
-            Ok(stmt) => return Some(stmt),
-            Err(e) => {
-                eprintln!("{}", e.message);
-                self.synchronize();
-                None
-            }
+        self.consume(TokenKind::LeftBrace, "Expect '{' before class body.")?;
+
+        let mut methods = Vec::new();
+        while !self.check(TokenKind::RightBrace) && !self.is_at_end() {
+            let method_start = self.peek().span();
+            methods.push(self.function("method", method_start)?);
         }
+
+        self.consume(TokenKind::RightBrace, "Expect '}' after class body.")?;
+
+        Ok(Stmt::Class(
+            name,
+            superclass,
+            methods,
+            self.span_from(start),
+        ))
     }
 
     fn statement(&mut self) -> Result<Stmt, ParseError> {
@@ -72,8 +184,12 @@ impl Parser {
             self.return_statement()
         } else if self.match_token(&[TokenKind::While]) {
             self.while_statement()
+        } else if self.match_token(&[TokenKind::Break, TokenKind::Continue]) {
+            self.loop_control_statement()
         } else if self.match_token(&[TokenKind::LeftBrace]) {
-            Ok(Stmt::Block(self.block()?))
+            let start = self.previous().span();
+            let statements = self.block()?;
+            Ok(Stmt::Block(statements, self.span_from(start)))
         } else {
             self.expression_statement()
         }
@@ -81,28 +197,49 @@ impl Parser {
 
     fn expression_statement(&mut self) -> Result<Stmt, ParseError> {
         let expr = self.expression()?;
+        if self.repl && self.nesting == 0 && self.is_at_end() {
+            let span = expr.span();
+            return Ok(Stmt::Expression(expr, span));
+        }
         self.consume(TokenKind::Semicolon, "Expect ';' after expression.")?;
-        Ok(Stmt::Expression(expr))
+        let span = expr.span().to(self.previous().span());
+        Ok(Stmt::Expression(expr, span))
     }
 
-    fn function(&mut self, kind: &str) -> Result<Stmt, ParseError> {
+    fn function(&mut self, kind: &str, start: Span) -> Result<Stmt, ParseError> {
         let name = self.consume(TokenKind::Identifier, &format!("Expect {} name.", kind))?;
 
         self.consume(
             TokenKind::LeftParen,
             &format!("Expect '(' after {} name.", kind),
         )?;
+        let (parameters, body) = self.parameters_and_body(kind)?;
 
+        Ok(Stmt::Function(
+            name,
+            parameters,
+            Rc::new(body),
+            self.span_from(start),
+        ))
+    }
+
+    /// Parses the parameter list, after its `(`, and the body of a named
+    /// function, method or lambda.
+    fn parameters_and_body(&mut self, kind: &str) -> Result<(Vec<Token>, Vec<Stmt>), ParseError> {
         let mut parameters = Vec::new();
 
         if !self.check(TokenKind::RightParen) {
             loop {
                 if parameters.len() >= 255 {
-                    return Err(self.error(self.peek(), "Can't have more than 255 parameters."));
+                    return Err(self.error(
+                        self.peek(),
+                        ParseErrorKind::TooManyParameters,
+                        "Can't have more than 255 parameters.",
+                    ));
                 }
 
                 parameters.push(self.consume(TokenKind::Identifier, "Expect parameter name.")?);
-                if self.match_token(&[TokenKind::Comma]) {
+                if !self.match_token(&[TokenKind::Comma]) {
                     break;
                 }
             }
@@ -114,9 +251,11 @@ impl Parser {
             &format!("Expect '{{' before {} body.", kind),
         )?;
 
-        let body = self.block()?;
-
-        Ok(Stmt::Function(name, parameters, body))
+        // A loop around the declaration doesn't extend into the body.
+        let enclosing_loops = mem::replace(&mut self.loop_depth, 0);
+        let body = self.block();
+        self.loop_depth = enclosing_loops;
+        Ok((parameters, body?))
     }
 
     fn expression(&mut self) -> Result<Expr, ParseError> {
@@ -138,12 +277,55 @@ impl Parser {
     }
 
     fn while_statement(&mut self) -> Result<Stmt, ParseError> {
+        let start = self.previous().span();
         self.consume(TokenKind::LeftParen, "Expect '(' after 'while'.")?;
         let condition = self.expression()?;
         self.consume(TokenKind::RightParen, "Expect ')' after condition.")?;
-        let body = self.statement()?;
+        let body = self.loop_body()?;
+
+        Ok(Stmt::While(
+            condition,
+            Box::new(body),
+            None,
+            self.span_from(start),
+        ))
+    }
+
+    fn loop_body(&mut self) -> Result<Stmt, ParseError> {
+        self.loop_depth += 1;
+        let body = self.nested_statement();
+        self.loop_depth -= 1;
+        body
+    }
 
-        Ok(Stmt::While(condition, Box::new(body)))
+    /// Parses the body of an `if`, `while` or `for`, where the REPL doesn't
+    /// let a trailing expression omit its semicolon.
+    fn nested_statement(&mut self) -> Result<Stmt, ParseError> {
+        self.nesting += 1;
+        let stmt = self.statement();
+        self.nesting -= 1;
+        stmt
+    }
+
+    fn loop_control_statement(&mut self) -> Result<Stmt, ParseError> {
+        let keyword = self.previous().clone();
+        if self.loop_depth == 0 {
+            return Err(self.error(
+                &keyword,
+                ParseErrorKind::OutsideLoop,
+                &format!("Can't use '{}' outside of a loop.", keyword.lexeme()),
+            ));
+        }
+
+        self.consume(
+            TokenKind::Semicolon,
+            &format!("Expect ';' after '{}'.", keyword.lexeme()),
+        )?;
+        let span = self.span_from(keyword.span());
+        Ok(match keyword.kind {
+            TokenKind::Break => Stmt::Break(keyword, span),
+            _ => Stmt::Continue(keyword, span),
+        })
     }
 
     fn return_statement(&mut self) -> Result<Stmt, ParseError> {
@@ -154,30 +336,39 @@ impl Parser {
         }
 
         self.consume(TokenKind::Semicolon, "Expect ';' after return value.")?;
-        Ok(Stmt::Return(keyword, value))
+        let span = self.span_from(keyword.span());
+        Ok(Stmt::Return(keyword, value, span))
     }
 
     fn print_statement(&mut self) -> Result<Stmt, ParseError> {
+        let start = self.previous().span();
         let value = self.expression()?;
         self.consume(TokenKind::Semicolon, "Expect ';' after value.")?;
-        Ok(Stmt::Print(value))
+        Ok(Stmt::Print(value, self.span_from(start)))
     }
 
     fn if_statement(&mut self) -> Result<Stmt, ParseError> {
+        let start = self.previous().span();
         self.consume(TokenKind::LeftParen, "Expect '(' after 'if'.")?;
         let condition = self.expression()?;
         self.consume(TokenKind::RightParen, "Expect ')' after if condition.")?;
 
-        let then_branch = self.statement()?;
+        let then_branch = self.nested_statement()?;
         let mut else_branch: Option<Box<Stmt>> = None;
         if self.match_token(&[TokenKind::Else]) {
-            else_branch = Some(Box::new(self.statement()?));
+            else_branch = Some(Box::new(self.nested_statement()?));
         }
 
-        Ok(Stmt::If(condition, Box::new(then_branch), else_branch))
+        Ok(Stmt::If(
+            condition,
+            Box::new(then_branch),
+            else_branch,
+            self.span_from(start),
+        ))
     }
 
     fn for_statement(&mut self) -> Result<Stmt, ParseError> {
+        let start = self.previous().span();
         self.consume(TokenKind::LeftParen, "Expect '(' after 'for'.")?;
 
         let initializer = if self.match_token(&[TokenKind::Semicolon]) {
@@ -202,24 +393,24 @@ impl Parser {
         };
         self.consume(TokenKind::RightParen, "Expect ')' after for clauses.")?;
 
-        let mut body = self.statement()?;
+        let body = self.loop_body()?;
 
-        if let Some(inc) = increment {
-            body = Stmt::Block(vec![body, Stmt::Expression(inc)]);
-        }
+        // The desugared nodes all take the span of the whole `for` statement.
+        let span = self.span_from(start);
 
-        let condition = condition.unwrap_or(Expr::Literal(Literal::Boolean(true)));
+        let condition = condition.unwrap_or(Expr::Literal(Literal::Boolean(true), span));
 
-        body = Stmt::While(condition, Box::new(body));
+        let mut body = Stmt::While(condition, Box::new(body), increment, span);
 
         if let Some(init) = initializer {
-            body = Stmt::Block(vec![init, body]);
+            body = Stmt::Block(vec![init, body], span);
         }
 
         Ok(body)
     }
 
     fn var_declaration(&mut self) -> Result<Stmt, ParseError> {
+        let start = self.previous().span();
         let name = self.consume(TokenKind::Identifier, "Expect variable name.")?;
 
         let mut initializer: Option<Expr> = None;
@@ -232,21 +423,105 @@ impl Parser {
             "Expect ';' after variable declaration.",
         )?;
 
-        Ok(Stmt::Var(name, initializer))
+        Ok(Stmt::Var(name, initializer, self.span_from(start)))
     }
 
     fn assignment(&mut self) -> Result<Expr, ParseError> {
-        let expr = self.or()?;
+        let expr = self.conditional()?;
 
         if self.match_token(&[TokenKind::Equal]) {
             let equals = self.previous().clone();
             let value = self.assignment()?;
 
-            if let Expr::Variable(token) = expr {
-                return Ok(Expr::Assign(token, Box::new(value)));
+            match expr {
+                Expr::Variable(token, id) => return Ok(Expr::Assign(token, Box::new(value), id)),
+                Expr::Get(object, name) => return Ok(Expr::Set(object, name, Box::new(value))),
+                Expr::Index(object, index, bracket) => {
+                    return Ok(Expr::SetIndex(object, index, bracket, Box::new(value)))
+                }
+                _ => {}
             }
 
-            return Err(self.error(&equals, "Invalid assignment target."));
+            return Err(self.error(
+                &equals,
+                ParseErrorKind::InvalidAssignmentTarget,
+                "Invalid assignment target.",
+            ));
+        }
+
+        if self.match_token(&[
+            TokenKind::PlusEqual,
+            TokenKind::MinusEqual,
+            TokenKind::StarEqual,
+            TokenKind::SlashEqual,
+            TokenKind::PercentEqual,
+        ]) {
+            let operator = self.previous().clone();
+            let value = self.assignment()?;
+            return self.update(expr, operator, value, false);
+        }
+
+        Ok(expr)
+    }
+
+    /// Builds a compound assignment or increment of `target`, which must be a
+    /// variable, a property or an indexed element.
+    fn update(
+        &self,
+        target: Expr,
+        operator: Token,
+        value: Expr,
+        postfix: bool,
+    ) -> Result<Expr, ParseError> {
+        match target {
+            Expr::Variable(..) | Expr::Get(..) | Expr::Index(..) => Ok(Expr::Update(
+                Box::new(target),
+                operator,
+                Box::new(value),
+                postfix,
+            )),
+            _ => Err(self.error(
+                &operator,
+                ParseErrorKind::InvalidAssignmentTarget,
+                "Invalid assignment target.",
+            )),
+        }
+    }
+
+    /// The `1` that `++` and `--` add or subtract.
+    fn one(operator: &Token) -> Expr {
+        Expr::Literal(Literal::Number(1.0), operator.span())
+    }
+
+    /// `cond ? a : b`, which nests to the right: `a ? b : c ? d : e` is
+    /// `a ? b : (c ? d : e)`.
+    fn conditional(&mut self) -> Result<Expr, ParseError> {
+        let condition = self.coalesce()?;
+
+        if self.match_token(&[TokenKind::Question]) {
+            let then_branch = self.expression()?;
+            self.consume(
+                TokenKind::Colon,
+                "Expect ':' after then branch of conditional expression.",
+            )?;
+            let else_branch = self.conditional()?;
+            return Ok(Expr::Conditional(
+                Box::new(condition),
+                Box::new(then_branch),
+                Box::new(else_branch),
+            ));
+        }
+
+        Ok(condition)
+    }
+
+    fn coalesce(&mut self) -> Result<Expr, ParseError> {
+        let mut expr = self.or()?;
+
+        while self.match_token(&[TokenKind::QuestionQuestion]) {
+            let operator = self.previous().clone();
+            let right = self.or()?;
+            expr = Expr::Logical(Box::new(expr), operator, Box::new(right));
         }
 
         Ok(expr)
@@ -257,7 +532,7 @@ impl Parser {
 
         while self.match_token(&[TokenKind::Or]) {
             let operator = self.previous().clone();
-            let right = self.equality()?;
+            let right = self.and()?;
             expr = Expr::Logical(Box::new(expr), operator, Box::new(right));
         }
 
@@ -281,7 +556,7 @@ impl Parser {
 
         while self.match_token(&[TokenKind::EqualEqual, TokenKind::BangEqual]) {
             let operator = self.previous().clone();
-            let right = self.unary()?;
+            let right = self.comparison()?;
             expr = Expr::Binary(Box::new(expr), operator, Box::new(right));
         }
 
@@ -298,7 +573,7 @@ impl Parser {
             TokenKind::LessEqual,
         ]) {
             let operator = self.previous().clone();
-            let right = self.unary()?;
+            let right = self.term()?;
             expr = Expr::Binary(Box::new(expr), operator, Box::new(right));
         }
 
@@ -320,7 +595,7 @@ impl Parser {
     fn factor(&mut self) -> Result<Expr, ParseError> {
         let mut expr = self.unary()?;
 
-        while self.match_token(&[TokenKind::Slash, TokenKind::Star]) {
+        while self.match_token(&[TokenKind::Slash, TokenKind::Star, TokenKind::Percent]) {
             let operator = self.previous().clone();
             let right = self.unary()?;
             expr = Expr::Binary(Box::new(expr), operator, Box::new(right));
@@ -336,20 +611,72 @@ impl Parser {
             return Ok(Expr::Unary(operator, Box::new(right)));
         }
 
-        self.call()
+        if self.match_token(&[TokenKind::PlusPlus, TokenKind::MinusMinus]) {
+            // The target is only what `++` and `--` can update, so in
+            // `++a ** 2` the updated value is what gets raised.
+            let operator = self.previous().clone();
+            let target = self.postfix()?;
+            let one = Self::one(&operator);
+            let update = self.update(target, operator, one, false)?;
+            return self.exponent(update);
+        }
+
+        let base = self.postfix()?;
+        self.exponent(base)
+    }
+
+    /// `**` binds tighter than a unary operator on its left, so `-2 ** 2` is
+    /// `-4`, and is right-associative.
+    fn exponent(&mut self, base: Expr) -> Result<Expr, ParseError> {
+        if self.match_token(&[TokenKind::StarStar]) {
+            let operator = self.previous().clone();
+            let power = self.unary()?;
+            return Ok(Expr::Binary(Box::new(base), operator, Box::new(power)));
+        }
+
+        Ok(base)
+    }
+
+    fn postfix(&mut self) -> Result<Expr, ParseError> {
+        let expr = self.call()?;
+
+        if self.match_token(&[TokenKind::PlusPlus, TokenKind::MinusMinus]) {
+            let operator = self.previous().clone();
+            let one = Self::one(&operator);
+            return self.update(expr, operator, one, true);
+        }
+
+        Ok(expr)
     }
 
     fn call(&mut self) -> Result<Expr, ParseError> {
         let mut expr = self.primary()?;
+        let mut optional = false;
 
         loop {
             if self.match_token(&[TokenKind::LeftParen]) {
                 expr = self.finish_call(expr)?;
+            } else if self.match_token(&[TokenKind::Dot]) {
+                let name =
+                    self.consume(TokenKind::Identifier, "Expect property name after '.'.")?;
+                expr = Expr::Get(Box::new(expr), name);
+            } else if self.match_token(&[TokenKind::QuestionDot]) {
+                let name =
+                    self.consume(TokenKind::Identifier, "Expect property name after '?.'.")?;
+                expr = Expr::OptionalGet(Box::new(expr), name);
+                optional = true;
+            } else if self.match_token(&[TokenKind::LeftBracket]) {
+                let index = self.expression()?;
+                let bracket = self.consume(TokenKind::RightBracket, "Expect ']' after index.")?;
+                expr = Expr::Index(Box::new(expr), Box::new(index), bracket);
             } else {
                 break;
             }
         }
 
+        if optional {
+            expr = Expr::OptionalChain(Box::new(expr));
+        }
         Ok(expr)
     }
 
@@ -358,7 +685,11 @@ impl Parser {
         if !self.check(TokenKind::RightParen) {
             loop {
                 if arguments.len() >= 255 {
-                    return Err(self.error(self.peek(), "Can't have more than 255 arguments."));
+                    return Err(self.error(
+                        self.peek(),
+                        ParseErrorKind::TooManyArguments,
+                        "Can't have more than 255 arguments.",
+                    ));
                 }
                 arguments.push(self.expression()?);
                 if !self.match_token(&[TokenKind::Comma]) {
@@ -374,29 +705,159 @@ impl Parser {
 
     fn primary(&mut self) -> Result<Expr, ParseError> {
         if self.match_token(&[TokenKind::False]) {
-            return Ok(Expr::Literal(Literal::Boolean(false)));
+            return Ok(Expr::Literal(
+                Literal::Boolean(false),
+                self.previous().span(),
+            ));
         }
         if self.match_token(&[TokenKind::True]) {
-            return Ok(Expr::Literal(Literal::Boolean(true)));
+            return Ok(Expr::Literal(
+                Literal::Boolean(true),
+                self.previous().span(),
+            ));
         }
         if self.match_token(&[TokenKind::Nil]) {
-            return Ok(Expr::Literal(Literal::Nil));
+            return Ok(Expr::Literal(Literal::Nil, self.previous().span()));
         }
         if self.match_token(&[TokenKind::Number, TokenKind::String]) {
-            return Ok(Expr::Literal(self.previous().literal.clone().unwrap()));
+            let token = self.previous();
+            return Ok(Expr::Literal(token.literal.clone().unwrap(), token.span()));
+        }
+
+        if self.match_token(&[TokenKind::Fun]) {
+            let keyword = self.previous().clone();
+            self.consume(TokenKind::LeftParen, "Expect '(' after 'fun'.")?;
+            let (parameters, body) = self.parameters_and_body("function")?;
+            let span = self.span_from(keyword.span());
+            return Ok(Expr::Lambda(keyword, parameters, Rc::new(body), span));
+        }
+
+        if self.match_token(&[TokenKind::Interpolation]) {
+            return self.interpolation();
+        }
+
+        if self.match_token(&[TokenKind::LeftBracket]) {
+            return self.list();
+        }
+
+        if self.match_token(&[TokenKind::LeftBrace]) {
+            return self.map();
+        }
+
+        if self.match_token(&[TokenKind::Super]) {
+            let keyword = self.previous().clone();
+            self.consume(TokenKind::Dot, "Expect '.' after 'super'.")?;
+            let method = self.consume(TokenKind::Identifier, "Expect superclass method name.")?;
+            return Ok(Expr::Super(keyword, method, next_expr_id()));
+        }
+
+        if self.match_token(&[TokenKind::This]) {
+            return Ok(Expr::This(self.previous().clone(), next_expr_id()));
         }
 
         if self.match_token(&[TokenKind::Identifier]) {
-            return Ok(Expr::Variable(self.previous().clone()));
+            return Ok(Expr::Variable(self.previous().clone(), next_expr_id()));
         }
 
         if self.match_token(&[TokenKind::LeftParen]) {
+            let start = self.previous().span();
             let expr = self.expression()?;
             self.consume(TokenKind::RightParen, "Expect ')' after expression.")?;
-            return Ok(Expr::Grouping(Box::new(expr)));
+            return Ok(Expr::Grouping(Box::new(expr), self.span_from(start)));
         }
 
-        Err(self.error(self.peek(), "Expect expression."))
+        Err(self.error(
+            self.peek(),
+            ParseErrorKind::ExpectedExpression,
+            "Expect expression.",
+        ))
+    }
+
+    /// Parses the rest of an interpolated string once its first
+    /// `Interpolation` token has been consumed. The parts alternate between
+    /// string literals and the expressions embedded between them.
+    fn interpolation(&mut self) -> Result<Expr, ParseError> {
+        let start = self.previous().span();
+        let mut parts = Vec::new();
+        loop {
+            let text = self.previous().clone();
+            parts.push(Expr::Literal(text.literal.clone().unwrap(), text.span()));
+            // Segments after an embedded expression start with its closing
+            // `}`, unlike a string literal inside the braces.
+            let next = self.peek();
+            if matches!(next.kind, TokenKind::String | TokenKind::Interpolation)
+                && next.lexeme().starts_with('}')
+            {
+                return Err(self.error(
+                    &Self::empty_interpolation(&text, next),
+                    ParseErrorKind::ExpectedExpression,
+                    "Expect expression inside '${}'.",
+                ));
+            }
+            parts.push(self.expression()?);
+
+            if self.match_token(&[TokenKind::Interpolation]) {
+                continue;
+            }
+            self.consume(
+                TokenKind::String,
+                "Expect '}' after interpolated expression.",
+            )?;
+            let text = self.previous();
+            parts.push(Expr::Literal(text.literal.clone().unwrap(), text.span()));
+            return Ok(Expr::Interpolation(parts, self.span_from(start)));
+        }
+    }
+
+    /// Builds a token covering an empty `${}`, from the `${` that ends
+    /// `opening` to the `}` that starts `closing`.
+    fn empty_interpolation(opening: &Token, closing: &Token) -> Token {
+        let start = opening.end - 2;
+        let before = &opening.source[..start];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        Token {
+            kind: closing.kind,
+            source: Rc::clone(&opening.source),
+            line: opening.line + opening.source[opening.start..start].matches('\n').count(),
+            literal: None,
+            start,
+            end: closing.start + 1,
+            column: before[line_start..].chars().count() + 1,
+        }
+    }
+
+    /// Parses the elements of a list literal once its `[` has been consumed.
+    /// A trailing comma is allowed after the last element.
+    fn list(&mut self) -> Result<Expr, ParseError> {
+        let start = self.previous().span();
+        let mut elements = Vec::new();
+        while !self.check(TokenKind::RightBracket) {
+            elements.push(self.expression()?);
+            if !self.match_token(&[TokenKind::Comma]) {
+                break;
+            }
+        }
+        self.consume(TokenKind::RightBracket, "Expect ']' after list elements.")?;
+        Ok(Expr::List(elements, self.span_from(start)))
+    }
+
+    /// Parses the `key: value` entries of a map literal once its `{` has been
+    /// consumed. A `{` that starts a statement is a block instead, so this is
+    /// only reached in expression position.
+    fn map(&mut self) -> Result<Expr, ParseError> {
+        let start = self.previous().span();
+        let mut entries = Vec::new();
+        while !self.check(TokenKind::RightBrace) {
+            let key = self.expression()?;
+            let colon = self.consume(TokenKind::Colon, "Expect ':' after map key.")?;
+            let value = self.expression()?;
+            entries.push((key, colon, value));
+            if !self.match_token(&[TokenKind::Comma]) {
+                break;
+            }
+        }
+        self.consume(TokenKind::RightBrace, "Expect '}' after map entries.")?;
+        Ok(Expr::Map(entries, self.span_from(start)))
     }
 
     fn match_token(&mut self, kinds: &[TokenKind]) -> bool {
@@ -413,7 +874,11 @@ impl Parser {
         if self.check(token_kind) {
             return Ok(self.advance());
         }
-        Err(self.error(self.peek(), message))
+        Err(self.error(
+            self.peek(),
+            ParseErrorKind::ExpectedToken(token_kind),
+            message,
+        ))
     }
 
     fn check(&self, token_kind: TokenKind) -> bool {
@@ -423,9 +888,27 @@ impl Parser {
         self.peek().kind == token_kind
     }
 
+    /// Whether the token after the current one has the given kind.
+    fn check_next(&mut self, token_kind: TokenKind) -> bool {
+        if self.is_at_end() {
+            return false;
+        }
+        if self.lookahead.is_none() {
+            self.lookahead = self.tokens.next();
+        }
+        self.lookahead
+            .as_ref()
+            .is_some_and(|token| token.kind == token_kind)
+    }
+
     fn advance(&mut self) -> Token {
         if !self.is_at_end() {
-            self.current += 1;
+            let next = self
+                .lookahead
+                .take()
+                .or_else(|| self.tokens.next())
+                .expect("token stream must end with an Eof token");
+            self.previous = Some(mem::replace(&mut self.current, next));
         }
         self.previous().clone()
     }
@@ -435,19 +918,25 @@ impl Parser {
     }
 
     fn peek(&self) -> &Token {
-        &self.tokens[self.current]
+        &self.current
     }
 
+    /// The most recently consumed token, or the current one before anything
+    /// has been consumed.
     fn previous(&self) -> &Token {
-        &self.tokens[self.current - 1]
+        self.previous.as_ref().unwrap_or(&self.current)
     }
 
-    fn error(&self, token: &Token, message: &str) -> ParseError {
+    /// Returns a span from `start` through the most recently consumed token.
+    fn span_from(&self, start: Span) -> Span {
+        start.to(self.previous().span())
+    }
+
+    fn error(&self, token: &Token, kind: ParseErrorKind, message: &str) -> ParseError {
         ParseError {
-            message: format!(
-                "[line {}] Error at '{}': {}",
-                token.line, token.lexeme, message
-            ),
+            token: token.clone(),
+            kind,
+            message: message.to_string(),
         }
     }
 
@@ -0,0 +1,180 @@
+use std::fs;
+use std::io::{self, Write};
+
+use crate::{
+    diagnostic::ErrorFormat,
+    interpreter::Interpreter,
+    parser::Parser,
+    run, run_repl,
+    scanner::{ScanErrorKind, Scanner},
+    token_kind::TokenKind,
+};
+
+const HELP: &str = "\
+:tokens <code>  show the tokens scanned from <code>
+:ast <code>     show the syntax tree parsed from <code>
+:env            list the global variables
+:load <file>    run <file> in this session
+:reset          forget everything defined so far
+:help           show this message
+:quit           leave the REPL";
+
+pub struct Repl {
+    interpreter: Interpreter,
+    format: ErrorFormat,
+}
+
+impl Default for Repl {
+    fn default() -> Self {
+        Self::new(ErrorFormat::Human)
+    }
+}
+
+impl Repl {
+    /// Creates a session that reports errors, including those from `:load`,
+    /// in `format`.
+    pub fn new(format: ErrorFormat) -> Self {
+        Repl {
+            interpreter: Interpreter::new(),
+            format,
+        }
+    }
+
+    pub fn interpreter(&self) -> &Interpreter {
+        &self.interpreter
+    }
+
+    pub fn run(&mut self) {
+        while let Some(input) = read_input() {
+            let input = input.trim();
+            if input.is_empty() {
+                continue;
+            }
+
+            if input.starts_with(':') {
+                if input == ":quit" {
+                    break;
+                }
+                let output = self.command(input);
+                if !output.is_empty() {
+                    println!("{}", output);
+                }
+                continue;
+            }
+
+            if let Ok(Some(value)) = run_repl(&mut self.interpreter, input, self.format) {
+                println!("{}", value);
+            }
+        }
+    }
+
+    /// Runs a `:`-prefixed meta-command and returns the text to show for it.
+    pub fn command(&mut self, input: &str) -> String {
+        let (name, argument) = match input.split_once(char::is_whitespace) {
+            Some((name, argument)) => (name, argument.trim()),
+            None => (input, ""),
+        };
+
+        match name {
+            ":tokens" => match Scanner::new(argument).scan_tokens() {
+                Ok(tokens) => tokens
+                    .iter()
+                    .map(|token| format!("{:>4} {:?} '{}'", token.line, token.kind, token.lexeme()))
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+                Err(errors) => join_lines(&errors),
+            },
+            ":ast" => match Scanner::new(argument).scan_tokens() {
+                Ok(tokens) => match Parser::new(tokens).parse_repl() {
+                    Ok(statements) => statements
+                        .iter()
+                        .map(|stmt| format!("{:#?}", stmt))
+                        .collect::<Vec<_>>()
+                        .join("\n"),
+                    Err(errors) => join_lines(&errors),
+                },
+                Err(errors) => join_lines(&errors),
+            },
+            ":env" => {
+                let globals = self.interpreter.globals.borrow();
+                let mut names: Vec<_> = globals.values().keys().collect();
+                names.sort();
+                names
+                    .into_iter()
+                    .map(|name| format!("{} = {}", name, globals.values()[name]))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            }
+            ":load" => match fs::read_to_string(argument) {
+                Ok(source) => {
+                    let _ = run(&mut self.interpreter, argument, &source, self.format);
+                    String::new()
+                }
+                Err(err) => format!("Error reading file {}: {}", argument, err),
+            },
+            ":reset" => {
+                self.interpreter = Interpreter::new();
+                String::new()
+            }
+            ":help" => HELP.to_string(),
+            _ => format!("Unknown command '{}'. Type :help for a list.", name),
+        }
+    }
+}
+
+fn join_lines<T: ToString>(items: &[T]) -> String {
+    items
+        .iter()
+        .map(|item| item.to_string())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Returns true once `source` has no open parentheses, braces, brackets,
+/// strings or block comments, so the REPL knows whether to wait for
+/// continuation lines.
+pub fn is_complete(source: &str) -> bool {
+    let mut scanner = Scanner::new(source);
+    let mut depth = 0;
+    for token in scanner.by_ref() {
+        match token.kind {
+            TokenKind::LeftParen | TokenKind::LeftBrace | TokenKind::LeftBracket => depth += 1,
+            TokenKind::RightParen | TokenKind::RightBrace | TokenKind::RightBracket => depth -= 1,
+            _ => {}
+        }
+    }
+
+    let unterminated = scanner.errors().iter().any(|err| {
+        matches!(
+            err.kind,
+            ScanErrorKind::UnterminatedString | ScanErrorKind::UnterminatedComment
+        )
+    });
+    depth <= 0 && !unterminated
+}
+
+/// Reads one logical input from stdin, following up with continuation lines
+/// until it is complete. Returns `None` at end of input.
+fn read_input() -> Option<String> {
+    let mut input = String::new();
+    let mut prompt = "> ";
+    loop {
+        print!("{}", prompt);
+        io::stdout().flush().unwrap();
+
+        let mut line = String::new();
+        let read = io::stdin()
+            .read_line(&mut line)
+            .expect("Failed to read line");
+        if read == 0 {
+            println!();
+            return if input.is_empty() { None } else { Some(input) };
+        }
+        input.push_str(&line);
+
+        if input.trim_start().starts_with(':') || is_complete(&input) {
+            return Some(input);
+        }
+        prompt = ". ";
+    }
+}
@@ -0,0 +1,374 @@
+use core::fmt;
+use std::collections::HashMap;
+
+use crate::{
+    expression::{Expr, ExprId},
+    interpreter::Interpreter,
+    statement::Stmt,
+    token::Token,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ResolveErrorKind {
+    ReadInOwnInitializer,
+    AlreadyDeclared,
+    ReturnFromTopLevel,
+    ReturnValueFromInitializer,
+    InheritFromSelf,
+    ThisOutsideClass,
+    SuperOutsideClass,
+    SuperWithoutSuperclass,
+}
+
+impl ResolveErrorKind {
+    pub fn message(&self) -> &'static str {
+        match self {
+            ResolveErrorKind::ReadInOwnInitializer => {
+                "Can't read local variable in its own initializer."
+            }
+            ResolveErrorKind::AlreadyDeclared => "Already a variable with this name in this scope.",
+            ResolveErrorKind::ReturnFromTopLevel => "Can't return from top-level code.",
+            ResolveErrorKind::ReturnValueFromInitializer => {
+                "Can't return a value from an initializer."
+            }
+            ResolveErrorKind::InheritFromSelf => "A class can't inherit from itself.",
+            ResolveErrorKind::ThisOutsideClass => "Can't use 'this' outside of a class.",
+            ResolveErrorKind::SuperOutsideClass => "Can't use 'super' outside of a class.",
+            ResolveErrorKind::SuperWithoutSuperclass => {
+                "Can't use 'super' in a class with no superclass."
+            }
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            ResolveErrorKind::ReadInOwnInitializer => "read-in-own-initializer",
+            ResolveErrorKind::AlreadyDeclared => "already-declared",
+            ResolveErrorKind::ReturnFromTopLevel => "return-from-top-level",
+            ResolveErrorKind::ReturnValueFromInitializer => "return-value-from-initializer",
+            ResolveErrorKind::InheritFromSelf => "inherit-from-self",
+            ResolveErrorKind::ThisOutsideClass => "this-outside-class",
+            ResolveErrorKind::SuperOutsideClass => "super-outside-class",
+            ResolveErrorKind::SuperWithoutSuperclass => "super-without-superclass",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ResolveError {
+    pub token: Token,
+    pub kind: ResolveErrorKind,
+    pub message: String,
+}
+
+impl fmt::Display for ResolveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "[line {}] Error at '{}': {}",
+            self.token.line,
+            self.token.lexeme(),
+            self.message
+        )
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum FunctionType {
+    None,
+    Function,
+    Initializer,
+    Method,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum ClassType {
+    None,
+    Class,
+    Subclass,
+}
+
+/// Walks the program before it runs, telling the interpreter how many scopes
+/// separate each local variable use from its declaration.
+pub struct Resolver<'a> {
+    interpreter: &'a mut Interpreter,
+    /// Innermost scope last. A name maps to `false` while its initializer is
+    /// being resolved and to `true` once it is ready for use.
+    scopes: Vec<HashMap<String, bool>>,
+    current_function: FunctionType,
+    current_class: ClassType,
+    errors: Vec<ResolveError>,
+}
+
+impl<'a> Resolver<'a> {
+    pub fn new(interpreter: &'a mut Interpreter) -> Self {
+        Resolver {
+            interpreter,
+            scopes: Vec::new(),
+            current_function: FunctionType::None,
+            current_class: ClassType::None,
+            errors: Vec::new(),
+        }
+    }
+
+    pub fn resolve(mut self, statements: &[Stmt]) -> Result<(), Vec<ResolveError>> {
+        self.resolve_statements(statements);
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(self.errors)
+        }
+    }
+
+    fn resolve_statements(&mut self, statements: &[Stmt]) {
+        for statement in statements {
+            self.resolve_statement(statement);
+        }
+    }
+
+    fn resolve_statement(&mut self, statement: &Stmt) {
+        match statement {
+            Stmt::Expression(expr, _) | Stmt::Print(expr, _) => self.resolve_expression(expr),
+            Stmt::Var(name, initializer, _) => {
+                self.declare(name);
+                if let Some(initializer) = initializer {
+                    self.resolve_expression(initializer);
+                }
+                self.define(name);
+            }
+            Stmt::Block(statements, _) => {
+                self.begin_scope();
+                self.resolve_statements(statements);
+                self.end_scope();
+            }
+            Stmt::If(condition, then_branch, else_branch, _) => {
+                self.resolve_expression(condition);
+                self.resolve_statement(then_branch);
+                if let Some(else_branch) = else_branch {
+                    self.resolve_statement(else_branch);
+                }
+            }
+            Stmt::While(condition, body, increment, _) => {
+                self.resolve_expression(condition);
+                self.resolve_statement(body);
+                if let Some(increment) = increment {
+                    self.resolve_expression(increment);
+                }
+            }
+            Stmt::Break(..) | Stmt::Continue(..) => {}
+            Stmt::Function(name, params, body, _) => {
+                self.declare(name);
+                self.define(name);
+                self.resolve_function(params, body, FunctionType::Function);
+            }
+            Stmt::Return(keyword, value, _) => {
+                if self.current_function == FunctionType::None {
+                    self.error(keyword, ResolveErrorKind::ReturnFromTopLevel);
+                }
+                if let Some(value) = value {
+                    if self.current_function == FunctionType::Initializer {
+                        self.error(keyword, ResolveErrorKind::ReturnValueFromInitializer);
+                    }
+                    self.resolve_expression(value);
+                }
+            }
+            Stmt::Class(name, superclass, methods, _) => {
+                self.resolve_class(name, superclass.as_ref(), methods)
+            }
+        }
+    }
+
+    fn resolve_class(&mut self, name: &Token, superclass: Option<&Expr>, methods: &[Stmt]) {
+        let enclosing_class = self.current_class;
+        self.current_class = ClassType::Class;
+
+        self.declare(name);
+        self.define(name);
+
+        if let Some(superclass) = superclass {
+            if let Expr::Variable(superclass_name, _) = superclass {
+                if superclass_name.lexeme() == name.lexeme() {
+                    self.error(superclass_name, ResolveErrorKind::InheritFromSelf);
+                }
+            }
+            self.current_class = ClassType::Subclass;
+            self.resolve_expression(superclass);
+
+            self.begin_scope();
+            self.scopes
+                .last_mut()
+                .unwrap()
+                .insert("super".to_string(), true);
+        }
+
+        self.begin_scope();
+        self.scopes
+            .last_mut()
+            .unwrap()
+            .insert("this".to_string(), true);
+
+        for method in methods {
+            if let Stmt::Function(method_name, params, body, _) = method {
+                let kind = if method_name.lexeme() == "init" {
+                    FunctionType::Initializer
+                } else {
+                    FunctionType::Method
+                };
+                self.resolve_function(params, body, kind);
+            }
+        }
+
+        self.end_scope();
+        if superclass.is_some() {
+            self.end_scope();
+        }
+
+        self.current_class = enclosing_class;
+    }
+
+    fn resolve_expression(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Literal(..) => {}
+            Expr::Variable(name, id) => {
+                if let Some(scope) = self.scopes.last() {
+                    if scope.get(name.lexeme()) == Some(&false) {
+                        self.error(name, ResolveErrorKind::ReadInOwnInitializer);
+                    }
+                }
+                self.resolve_local(name, *id);
+            }
+            Expr::Assign(name, value, id) => {
+                self.resolve_expression(value);
+                self.resolve_local(name, *id);
+            }
+            Expr::Binary(left, _, right) | Expr::Logical(left, _, right) => {
+                self.resolve_expression(left);
+                self.resolve_expression(right);
+            }
+            Expr::Unary(_, right) => self.resolve_expression(right),
+            Expr::Grouping(expr, _) | Expr::OptionalChain(expr) => self.resolve_expression(expr),
+            Expr::Update(target, _, value, _) => {
+                self.resolve_expression(value);
+                self.resolve_expression(target);
+            }
+            Expr::Interpolation(parts, _) => {
+                for part in parts {
+                    self.resolve_expression(part);
+                }
+            }
+            Expr::Call(callee, _, arguments) => {
+                self.resolve_expression(callee);
+                for argument in arguments {
+                    self.resolve_expression(argument);
+                }
+            }
+            Expr::Get(object, _) | Expr::OptionalGet(object, _) => self.resolve_expression(object),
+            Expr::Lambda(_, params, body, _) => {
+                self.resolve_function(params, body, FunctionType::Function)
+            }
+            Expr::Conditional(condition, then_branch, else_branch) => {
+                self.resolve_expression(condition);
+                self.resolve_expression(then_branch);
+                self.resolve_expression(else_branch);
+            }
+            Expr::List(elements, _) => {
+                for element in elements {
+                    self.resolve_expression(element);
+                }
+            }
+            Expr::Map(entries, _) => {
+                for (key, _, value) in entries {
+                    self.resolve_expression(key);
+                    self.resolve_expression(value);
+                }
+            }
+            Expr::Index(object, index, _) => {
+                self.resolve_expression(object);
+                self.resolve_expression(index);
+            }
+            Expr::SetIndex(object, index, _, value) => {
+                self.resolve_expression(object);
+                self.resolve_expression(index);
+                self.resolve_expression(value);
+            }
+            Expr::Set(object, _, value) => {
+                self.resolve_expression(value);
+                self.resolve_expression(object);
+            }
+            Expr::This(keyword, id) => {
+                if self.current_class == ClassType::None {
+                    self.error(keyword, ResolveErrorKind::ThisOutsideClass);
+                    return;
+                }
+                self.resolve_local(keyword, *id);
+            }
+            Expr::Super(keyword, _, id) => {
+                match self.current_class {
+                    ClassType::None => self.error(keyword, ResolveErrorKind::SuperOutsideClass),
+                    ClassType::Class => {
+                        self.error(keyword, ResolveErrorKind::SuperWithoutSuperclass)
+                    }
+                    ClassType::Subclass => {}
+                }
+                self.resolve_local(keyword, *id);
+            }
+        }
+    }
+
+    fn resolve_function(&mut self, params: &[Token], body: &[Stmt], kind: FunctionType) {
+        let enclosing_function = self.current_function;
+        self.current_function = kind;
+
+        self.begin_scope();
+        for param in params {
+            self.declare(param);
+            self.define(param);
+        }
+        self.resolve_statements(body);
+        self.end_scope();
+
+        self.current_function = enclosing_function;
+    }
+
+    fn resolve_local(&mut self, name: &Token, id: ExprId) {
+        for (depth, scope) in self.scopes.iter().rev().enumerate() {
+            if scope.contains_key(name.lexeme()) {
+                self.interpreter.resolve(id, depth);
+                return;
+            }
+        }
+    }
+
+    fn begin_scope(&mut self) {
+        self.scopes.push(HashMap::new());
+    }
+
+    fn end_scope(&mut self) {
+        self.scopes.pop();
+    }
+
+    fn declare(&mut self, name: &Token) {
+        let Some(scope) = self.scopes.last_mut() else {
+            return;
+        };
+        if scope.contains_key(name.lexeme()) {
+            self.error(name, ResolveErrorKind::AlreadyDeclared);
+            return;
+        }
+        scope.insert(name.lexeme().to_string(), false);
+    }
+
+    fn define(&mut self, name: &Token) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name.lexeme().to_string(), true);
+        }
+    }
+
+    fn error(&mut self, token: &Token, kind: ResolveErrorKind) {
+        self.errors.push(ResolveError {
+            token: token.clone(),
+            kind,
+            message: kind.message().to_string(),
+        });
+    }
+}
@@ -1,37 +1,153 @@
-use crate::{literal::Literal, token::Token, token_kind::TokenKind};
+use core::fmt;
+use std::{collections::VecDeque, mem, rc::Rc};
 
+use crate::{literal::Literal, span::Span, token::Token, token_kind::TokenKind};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScanErrorKind {
+    UnexpectedCharacter,
+    UnterminatedString,
+    UnterminatedComment,
+    InvalidEscape,
+    InvalidUnicodeEscape,
+    MalformedNumber,
+}
+
+impl ScanErrorKind {
+    pub fn message(&self) -> &'static str {
+        match self {
+            ScanErrorKind::UnexpectedCharacter => "Unexpected character.",
+            ScanErrorKind::UnterminatedString => "Unterminated string.",
+            ScanErrorKind::UnterminatedComment => "Unterminated block comment.",
+            ScanErrorKind::InvalidEscape => "Invalid escape sequence.",
+            ScanErrorKind::InvalidUnicodeEscape => "Invalid Unicode escape sequence.",
+            ScanErrorKind::MalformedNumber => "Malformed number literal.",
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            ScanErrorKind::UnexpectedCharacter => "unexpected-character",
+            ScanErrorKind::UnterminatedString => "unterminated-string",
+            ScanErrorKind::UnterminatedComment => "unterminated-comment",
+            ScanErrorKind::InvalidEscape => "invalid-escape",
+            ScanErrorKind::InvalidUnicodeEscape => "invalid-unicode-escape",
+            ScanErrorKind::MalformedNumber => "malformed-number",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScanError {
+    pub kind: ScanErrorKind,
+    pub line: usize,
+    pub column: usize,
+    /// Byte offset of the offending text.
+    pub start: usize,
+    /// The offending source text.
+    pub text: String,
+}
+
+impl ScanError {
+    pub fn span(&self) -> Span {
+        Span::new(
+            self.start,
+            self.start + self.text.len(),
+            self.line,
+            self.column,
+        )
+    }
+}
+
+impl fmt::Display for ScanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "[line {}:{}] Error at '{}': {}",
+            self.line,
+            self.column,
+            self.text,
+            self.kind.message()
+        )
+    }
+}
+
+/// An interpolated string waiting for the `}` that ends its current `${`.
+struct OpenInterpolation {
+    /// Braces opened inside the interpolated expression and not yet closed.
+    braces: usize,
+    /// Where the part of the string before the `${` begins.
+    start: usize,
+    line: usize,
+    column: usize,
+}
+
+/// Turns source text into tokens in a single pass. `start` and `current` are
+/// byte offsets that always sit on character boundaries.
+///
+/// The scanner is an iterator that scans lazily and yields a final `Eof`
+/// token; lexical errors are collected on the side and read with
+/// [`Scanner::errors`].
 pub struct Scanner {
     pub start: usize,
     pub current: usize,
     pub line: usize,
-    pub source: String,
-    tokens: Vec<Token>,
+    /// Column of the character at `current`, counting characters from 1.
+    column: usize,
+    /// Line and column where the token being scanned begins.
+    start_line: usize,
+    start_column: usize,
+    pub source: Rc<str>,
+    /// Tokens scanned but not yet yielded.
+    tokens: VecDeque<Token>,
+    errors: Vec<ScanError>,
+    /// Innermost last.
+    interpolations: Vec<OpenInterpolation>,
+    /// Set once `Eof` has been yielded.
+    finished: bool,
 }
 
 impl Scanner {
-    pub fn new(source: &String) -> Self {
-        Self {
+    /// Passing an `Rc<str>` shares the source with the scanner and its tokens
+    /// instead of copying it.
+    ///
+    /// A leading `#!` line is skipped so scripts can be made executable.
+    pub fn new(source: impl Into<Rc<str>>) -> Self {
+        let mut scanner = Self {
             line: 1,
+            column: 1,
+            start_line: 1,
+            start_column: 1,
             current: 0,
             start: 0,
-            source: source.clone(),
-            tokens: Vec::new(),
+            source: source.into(),
+            tokens: VecDeque::new(),
+            errors: Vec::new(),
+            interpolations: Vec::new(),
+            finished: false,
+        };
+
+        if scanner.source.starts_with("#!") {
+            while scanner.peek() != '\n' && !scanner.is_at_end() {
+                scanner.advance();
+            }
         }
+        scanner
     }
 
-    pub fn scan_tokens(&mut self) -> Vec<Token> {
-        while !self.is_at_end() {
-            self.start = self.current;
-            self.scan_token();
+    /// Scans the whole source, returning every lexical error found if any.
+    pub fn scan_tokens(&mut self) -> Result<Vec<Token>, Vec<ScanError>> {
+        let tokens = self.by_ref().collect();
+        if self.errors.is_empty() {
+            Ok(tokens)
+        } else {
+            Err(mem::take(&mut self.errors))
         }
+    }
 
-        self.tokens.push(Token {
-            lexeme: "".to_string(),
-            line: self.line,
-            kind: TokenKind::Eof,
-            literal: None,
-        });
-        self.tokens.clone()
+    /// The lexical errors found in the part of the source scanned so far.
+    pub fn errors(&self) -> &[ScanError] {
+        &self.errors
     }
 
     pub fn scan_token(&mut self) {
@@ -39,14 +155,77 @@ impl Scanner {
         match c {
             '(' => self.add_token_no_literal(TokenKind::LeftParen),
             ')' => self.add_token_no_literal(TokenKind::RightParen),
-            '{' => self.add_token_no_literal(TokenKind::LeftBrace),
-            '}' => self.add_token_no_literal(TokenKind::RightBrace),
+            '{' => {
+                if let Some(open) = self.interpolations.last_mut() {
+                    open.braces += 1;
+                }
+                self.add_token_no_literal(TokenKind::LeftBrace);
+            }
+            '}' => match self.interpolations.last_mut() {
+                Some(open) if open.braces == 0 => {
+                    self.interpolations.pop();
+                    self.string();
+                }
+                Some(open) => {
+                    open.braces -= 1;
+                    self.add_token_no_literal(TokenKind::RightBrace);
+                }
+                None => self.add_token_no_literal(TokenKind::RightBrace),
+            },
+            '[' => self.add_token_no_literal(TokenKind::LeftBracket),
+            ']' => self.add_token_no_literal(TokenKind::RightBracket),
             ',' => self.add_token_no_literal(TokenKind::Comma),
+            ':' => self.add_token_no_literal(TokenKind::Colon),
+            '?' => {
+                let kind = if self.match_char('?') {
+                    TokenKind::QuestionQuestion
+                } else if self.match_char('.') {
+                    TokenKind::QuestionDot
+                } else {
+                    TokenKind::Question
+                };
+                self.add_token_no_literal(kind);
+            }
             '.' => self.add_token_no_literal(TokenKind::Dot),
-            '-' => self.add_token_no_literal(TokenKind::Minus),
-            '+' => self.add_token_no_literal(TokenKind::Plus),
+            '-' => {
+                let kind = if self.match_char('-') {
+                    TokenKind::MinusMinus
+                } else if self.match_char('=') {
+                    TokenKind::MinusEqual
+                } else {
+                    TokenKind::Minus
+                };
+                self.add_token_no_literal(kind);
+            }
+            '+' => {
+                let kind = if self.match_char('+') {
+                    TokenKind::PlusPlus
+                } else if self.match_char('=') {
+                    TokenKind::PlusEqual
+                } else {
+                    TokenKind::Plus
+                };
+                self.add_token_no_literal(kind);
+            }
             ';' => self.add_token_no_literal(TokenKind::Semicolon),
-            '*' => self.add_token_no_literal(TokenKind::Star),
+            '*' => {
+                let kind = if self.match_char('*') {
+                    TokenKind::StarStar
+                } else if self.match_char('=') {
+                    TokenKind::StarEqual
+                } else {
+                    TokenKind::Star
+                };
+                self.add_token_no_literal(kind);
+            }
+            '%' => {
+                let kind = if self.match_char('=') {
+                    TokenKind::PercentEqual
+                } else {
+                    TokenKind::Percent
+                };
+                self.add_token_no_literal(kind);
+            }
             '!' => {
                 let kind = if self.match_char('=') {
                     TokenKind::BangEqual
@@ -85,56 +264,106 @@ impl Scanner {
                         self.advance();
                     }
                 } else if self.match_char('*') {
-                    while self.peek() != '*' && self.peek_next() != '/' && !self.is_at_end() {
-                        if self.peek() == '\n' {
-                            self.line += 1;
-                        }
-                        self.advance();
-                    }
-                    self.advance();
-                    self.advance();
-                }else {
+                    self.block_comment();
+                } else if self.match_char('=') {
+                    self.add_token_no_literal(TokenKind::SlashEqual);
+                } else {
                     self.add_token_no_literal(TokenKind::Slash);
                 }
             }
             ' ' | '\r' | '\t' => {}
-            '\n' => self.line += 1,
+            '\n' => self.new_line(),
             '"' => self.string(),
             '0'..='9' => self.number(),
-            'a'..='z' | 'A'..='Z' | '_' => self.identifier(),
-            _ => println!("Unexpected character"),
+            c if is_identifier_start(c) => self.identifier(),
+            _ => self.error(ScanErrorKind::UnexpectedCharacter),
         }
     }
 
+    /// Skips a `/* ... */` comment, which may contain nested block comments.
+    fn block_comment(&mut self) {
+        let mut depth = 1;
+        while depth > 0 {
+            if self.is_at_end() {
+                self.error(ScanErrorKind::UnterminatedComment);
+                return;
+            }
+
+            if self.peek() == '/' && self.peek_next() == '*' {
+                self.advance();
+                self.advance();
+                depth += 1;
+            } else if self.peek() == '*' && self.peek_next() == '/' {
+                self.advance();
+                self.advance();
+                depth -= 1;
+            } else if self.advance() == '\n' {
+                self.new_line();
+            }
+        }
+    }
+
+    fn eof(&self) -> Token {
+        Token {
+            kind: TokenKind::Eof,
+            source: Rc::clone(&self.source),
+            line: self.line,
+            literal: None,
+            start: self.current,
+            end: self.current,
+            column: self.column,
+        }
+    }
+
+    fn new_line(&mut self) {
+        self.line += 1;
+        self.column = 1;
+    }
+
+    /// Records an error covering the text of the token being scanned.
+    fn error(&mut self, kind: ScanErrorKind) {
+        self.error_at(kind, self.start, self.start_line, self.start_column);
+    }
+
+    /// Records an error covering the text from `start` up to `current`.
+    fn error_at(&mut self, kind: ScanErrorKind, start: usize, line: usize, column: usize) {
+        self.errors.push(ScanError {
+            kind,
+            line,
+            column,
+            start,
+            text: self.source[start..self.current].to_string(),
+        });
+    }
+
+    /// Consumes the next character, or returns `'\0'` without moving at the
+    /// end of the source.
     pub fn advance(&mut self) -> char {
-        self.current += 1;
-        self.source.chars().nth(self.current - 1).unwrap()
+        let Some(c) = self.source[self.current..].chars().next() else {
+            return '\0';
+        };
+        self.current += c.len_utf8();
+        self.column += 1;
+        c
     }
 
     fn match_char(&mut self, expected: char) -> bool {
-        if self.is_at_end() {
-            return false;
-        }
-        if self.source.chars().nth(self.current).unwrap() != expected {
+        if self.peek() != expected || self.is_at_end() {
             return false;
         }
 
-        self.current += 1;
+        self.advance();
         true
     }
 
     fn peek(&self) -> char {
-        if self.is_at_end() {
-            return '\0';
-        }
-        self.source.chars().nth(self.current).unwrap()
+        self.source[self.current..].chars().next().unwrap_or('\0')
     }
 
     fn peek_next(&self) -> char {
-        if self.current + 1 >= self.source.len() {
-            return '\0';
-        }
-        self.source.chars().nth(self.current + 1).unwrap()
+        let mut chars = self.source[self.current..].chars();
+        chars.next();
+        chars.next().unwrap_or('\0')
     }
 
     fn is_at_end(&self) -> bool {
@@ -145,52 +374,202 @@ impl Scanner {
         self.add_token(kind, None)
     }
 
+    /// Scans a string literal, or the part of one up to a `${` or between an
+    /// interpolated expression's `}` and the next `${` or closing quote.
+    /// Parts ending in `${` become `Interpolation` tokens.
     fn string(&mut self) {
+        let mut value = String::new();
         while self.peek() != '"' && !self.is_at_end() {
-            if self.peek() == '\n' {
-                self.line += 1;
+            if self.peek() == '$' && self.peek_next() == '{' {
+                self.advance();
+                self.advance();
+                self.interpolations.push(OpenInterpolation {
+                    braces: 0,
+                    start: self.start,
+                    line: self.start_line,
+                    column: self.start_column,
+                });
+                self.add_token(TokenKind::Interpolation, Some(Literal::String(value)));
+                return;
+            }
+
+            match self.advance() {
+                '\\' => self.escape(&mut value),
+                c => {
+                    if c == '\n' {
+                        self.new_line();
+                    }
+                    value.push(c);
+                }
             }
-            self.advance();
         }
 
         if self.is_at_end() {
-            println!("Unterminated string");
+            self.error(ScanErrorKind::UnterminatedString);
             return;
         }
 
         self.advance();
-
-        let value = self.source[self.start + 1..self.current - 1].to_string();
         self.add_token(TokenKind::String, Some(Literal::String(value)));
     }
 
-    fn number(&mut self) {
-        while self.peek().is_digit(10) {
-            self.advance();
+    /// Decodes the escape sequence after a `\\` that has just been consumed,
+    /// reporting an error at the backslash if it is not valid.
+    fn escape(&mut self, value: &mut String) {
+        if self.is_at_end() {
+            return;
+        }
+
+        let (start, line, column) = (self.current - 1, self.line, self.column - 1);
+        let decoded = match self.advance() {
+            'n' => Ok('\n'),
+            't' => Ok('\t'),
+            'r' => Ok('\r'),
+            '\\' => Ok('\\'),
+            '"' => Ok('"'),
+            '0' => Ok('\0'),
+            '$' => Ok('$'),
+            'u' => self.unicode_escape(),
+            c => {
+                if c == '\n' {
+                    self.new_line();
+                }
+                Err(ScanErrorKind::InvalidEscape)
+            }
+        };
+
+        match decoded {
+            Ok(c) => value.push(c),
+            Err(kind) => self.error_at(kind, start, line, column),
+        }
+    }
+
+    /// Decodes the `{XXXX}` part of a `\\u{XXXX}` escape: one to six hex
+    /// digits naming a Unicode scalar value.
+    fn unicode_escape(&mut self) -> Result<char, ScanErrorKind> {
+        if !self.match_char('{') {
+            return Err(ScanErrorKind::InvalidUnicodeEscape);
         }
 
-        if self.peek() == '.' && self.peek_next().is_digit(10) {
+        let digits_start = self.current;
+        while self.peek().is_ascii_hexdigit() {
             self.advance();
-            while self.peek().is_digit(10) {
+        }
+        let digits = &self.source[digits_start..self.current];
+        let code = match digits.len() {
+            1..=6 => u32::from_str_radix(digits, 16).ok(),
+            _ => None,
+        };
+
+        if !self.match_char('}') {
+            return Err(ScanErrorKind::InvalidUnicodeEscape);
+        }
+        code.and_then(char::from_u32)
+            .ok_or(ScanErrorKind::InvalidUnicodeEscape)
+    }
+
+    /// Scans a decimal number, or a `0x` hexadecimal or `0b` binary integer.
+    /// Digits may be separated by single underscores, as in `1_000`.
+    fn number(&mut self) {
+        let radix = match (&self.source[self.start..self.current], self.peek()) {
+            ("0", 'x' | 'X') => Some(16),
+            ("0", 'b' | 'B') => Some(2),
+            _ => None,
+        };
+        let mut value = match radix {
+            Some(radix) => {
                 self.advance();
+                self.integer(radix)
             }
+            None => self.decimal(),
+        };
+
+        // Letters or digits running on from the literal, as in `0b102` or
+        // `12px`, are part of the mistake rather than a new token.
+        if is_identifier_continue(self.peek()) {
+            while is_identifier_continue(self.peek()) {
+                self.advance();
+            }
+            value = None;
         }
 
-        let value = self.source[self.start..self.current]
-            .parse::<f64>()
-            .unwrap();
-        self.add_token(TokenKind::Number, Some(Literal::Number(value)));
+        match value {
+            Some(value) => self.add_token(TokenKind::Number, Some(Literal::Number(value))),
+            None => self.error(ScanErrorKind::MalformedNumber),
+        }
+    }
+
+    fn integer(&mut self, radix: u32) -> Option<f64> {
+        let mut digits = String::new();
+        if !self.digits(radix, false, &mut digits) {
+            return None;
+        }
+        Some(digits.chars().fold(0.0, |value, c| {
+            value * radix as f64 + c.to_digit(radix).unwrap() as f64
+        }))
+    }
+
+    /// Scans the rest of a decimal number whose first digit has been consumed,
+    /// with an optional fraction and exponent.
+    fn decimal(&mut self) -> Option<f64> {
+        let mut text = self.source[self.start..self.current].to_string();
+        let mut valid = self.digits(10, true, &mut text);
+
+        if self.peek() == '.' && self.peek_next().is_ascii_digit() {
+            text.push(self.advance());
+            valid &= self.digits(10, false, &mut text);
+        }
+
+        if matches!(self.peek(), 'e' | 'E') {
+            text.push(self.advance());
+            if matches!(self.peek(), '+' | '-') {
+                text.push(self.advance());
+            }
+            valid &= self.digits(10, false, &mut text);
+        }
+
+        if valid {
+            text.parse().ok()
+        } else {
+            None
+        }
+    }
+
+    /// Consumes a run of digits in `radix` and underscores, appending the
+    /// digits to `text`. Returns false unless the run ends in a digit and
+    /// every underscore follows one; `after_digit` says whether a digit was
+    /// consumed just before the run.
+    fn digits(&mut self, radix: u32, after_digit: bool, text: &mut String) -> bool {
+        let mut valid = true;
+        let mut last_was_digit = after_digit;
+        loop {
+            match self.peek() {
+                '_' => {
+                    valid &= last_was_digit;
+                    last_was_digit = false;
+                }
+                c if c.is_digit(radix) => {
+                    text.push(c);
+                    last_was_digit = true;
+                }
+                _ => break,
+            }
+            self.advance();
+        }
+        valid && last_was_digit
     }
 
     fn identifier(&mut self) {
-        while self.peek().is_alphanumeric() {
+        while is_identifier_continue(self.peek()) {
             self.advance();
         }
 
         let text = &self.source[self.start..self.current];
         let kind = match text {
             "and" => TokenKind::And,
+            "break" => TokenKind::Break,
             "class" => TokenKind::Class,
+            "continue" => TokenKind::Continue,
             "else" => TokenKind::Else,
             "false" => TokenKind::False,
             "for" => TokenKind::For,
@@ -212,12 +591,54 @@ impl Scanner {
     }
 
     fn add_token(&mut self, kind: TokenKind, literal: Option<Literal>) {
-        let token = self.source[self.start..self.current].to_string();
-        self.tokens.push(Token {
-            lexeme: token,
-            line: self.line,
+        self.tokens.push_back(Token {
             kind,
+            source: Rc::clone(&self.source),
+            line: self.start_line,
             literal,
+            start: self.start,
+            end: self.current,
+            column: self.start_column,
         })
     }
 }
+
+impl Iterator for Scanner {
+    type Item = Token;
+
+    fn next(&mut self) -> Option<Token> {
+        loop {
+            if let Some(token) = self.tokens.pop_front() {
+                return Some(token);
+            }
+            if self.is_at_end() {
+                while let Some(open) = self.interpolations.pop() {
+                    self.error_at(
+                        ScanErrorKind::UnterminatedString,
+                        open.start,
+                        open.line,
+                        open.column,
+                    );
+                }
+                if self.finished {
+                    return None;
+                }
+                self.finished = true;
+                return Some(self.eof());
+            }
+
+            self.start = self.current;
+            self.start_line = self.line;
+            self.start_column = self.column;
+            self.scan_token();
+        }
+    }
+}
+
+fn is_identifier_start(c: char) -> bool {
+    c.is_alphabetic() || c == '_'
+}
+
+fn is_identifier_continue(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
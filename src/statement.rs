@@ -1,13 +1,40 @@
-use crate::{expression::Expr, token::Token};
+use std::rc::Rc;
 
+use crate::{expression::Expr, span::Span, token::Token};
+
+/// Statements. The trailing `Span` of each variant covers the whole
+/// statement, from its first token through its `;` or closing `}`.
 #[derive(Debug, Clone)]
 pub enum Stmt {
-    Expression(Expr),
-    Print(Expr),
-    Var(Token, Option<Expr>),
-    Block(Vec<Stmt>),
-    If(Expr, Box<Stmt>, Option<Box<Stmt>>),
-    While(Expr, Box<Stmt>),
-    Function(Token, Vec<Token>, Vec<Stmt>),
-    Return(Token, Option<Expr>),
+    Expression(Expr, Span),
+    Print(Expr, Span),
+    Var(Token, Option<Expr>, Span),
+    Block(Vec<Stmt>, Span),
+    If(Expr, Box<Stmt>, Option<Box<Stmt>>, Span),
+    /// A loop's condition, body and, for a `for` loop, the increment run
+    /// after each iteration, including ones ended by `continue`.
+    While(Expr, Box<Stmt>, Option<Expr>, Span),
+    Function(Token, Vec<Token>, Rc<Vec<Stmt>>, Span),
+    Return(Token, Option<Expr>, Span),
+    Class(Token, Option<Expr>, Vec<Stmt>, Span),
+    Break(Token, Span),
+    Continue(Token, Span),
+}
+
+impl Stmt {
+    pub fn span(&self) -> Span {
+        match self {
+            Stmt::Expression(_, span)
+            | Stmt::Print(_, span)
+            | Stmt::Var(_, _, span)
+            | Stmt::Block(_, span)
+            | Stmt::If(_, _, _, span)
+            | Stmt::While(_, _, _, span)
+            | Stmt::Function(_, _, _, span)
+            | Stmt::Return(_, _, span)
+            | Stmt::Class(_, _, _, span)
+            | Stmt::Break(_, span)
+            | Stmt::Continue(_, span) => *span,
+        }
+    }
 }
@@ -1,9 +1,44 @@
-use crate::{literal::Literal, token_kind::TokenKind};
+use std::{fmt, rc::Rc};
 
-#[derive(Clone, Debug)]
+use crate::{literal::Literal, span::Span, token_kind::TokenKind};
+
+/// A token refers back into the source it was scanned from instead of owning
+/// a copy of its lexeme, so cloning one is cheap.
+#[derive(Clone)]
 pub struct Token {
     pub kind: TokenKind,
-    pub lexeme: String,
+    /// The whole source the token was scanned from.
+    pub source: Rc<str>,
     pub line: usize,
     pub literal: Option<Literal>,
+    /// Byte offset of the first character of the lexeme.
+    pub start: usize,
+    /// Byte offset just past the end of the lexeme.
+    pub end: usize,
+    /// Column of the first character of the lexeme, counting from 1.
+    pub column: usize,
+}
+
+impl Token {
+    pub fn lexeme(&self) -> &str {
+        &self.source[self.start..self.end]
+    }
+
+    pub fn span(&self) -> Span {
+        Span::new(self.start, self.end, self.line, self.column)
+    }
+}
+
+impl fmt::Debug for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Token")
+            .field("kind", &self.kind)
+            .field("lexeme", &self.lexeme())
+            .field("line", &self.line)
+            .field("literal", &self.literal)
+            .field("start", &self.start)
+            .field("end", &self.end)
+            .field("column", &self.column)
+            .finish()
+    }
 }
@@ -5,10 +5,15 @@ pub enum TokenKind {
     RightParen,
     LeftBrace,
     RightBrace,
+    LeftBracket,
+    RightBracket,
+    Colon,
     Comma,
     Dot,
     Minus,
+    Percent,
     Plus,
+    Question,
     Semicolon,
     Slash,
     Star,
@@ -22,15 +27,29 @@ pub enum TokenKind {
     GreaterEqual,
     Less,
     LessEqual,
+    MinusEqual,
+    MinusMinus,
+    PercentEqual,
+    PlusEqual,
+    PlusPlus,
+    QuestionDot,
+    QuestionQuestion,
+    SlashEqual,
+    StarEqual,
+    StarStar,
 
     // Literals.
     Identifier,
     String,
+    /// The part of an interpolated string before a `${`.
+    Interpolation,
     Number,
 
     // Keywords.
     And,
+    Break,
     Class,
+    Continue,
     Else,
     False,
     Fun,
@@ -48,3 +67,18 @@ pub enum TokenKind {
 
     Eof,
 }
+
+impl TokenKind {
+    /// For a compound assignment or increment operator, the binary operator
+    /// it applies, as `Plus` for `+=` and `++`.
+    pub fn compound_operator(&self) -> Option<TokenKind> {
+        match self {
+            TokenKind::PlusEqual | TokenKind::PlusPlus => Some(TokenKind::Plus),
+            TokenKind::MinusEqual | TokenKind::MinusMinus => Some(TokenKind::Minus),
+            TokenKind::StarEqual => Some(TokenKind::Star),
+            TokenKind::SlashEqual => Some(TokenKind::Slash),
+            TokenKind::PercentEqual => Some(TokenKind::Percent),
+            _ => None,
+        }
+    }
+}
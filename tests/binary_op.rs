@@ -1,3 +1,5 @@
+use std::rc::Rc;
+
 use rslox::expression::Expr;
 use rslox::literal::Literal;
 use rslox::parser::Parser;
@@ -7,70 +9,101 @@ use rslox::{token::Token, token_kind::TokenKind};
 #[test]
 fn test_print_statement_parsing() {
     // Sample tokens for the expression: print 10 + 10 * 10;
+    let source: Rc<str> = Rc::from("print 10 + 10 * 10;");
     let tokens = vec![
         Token {
             kind: TokenKind::Print,
-            lexeme: "print".to_string(),
+            source: Rc::clone(&source),
             line: 1,
             literal: None,
+            start: 0,
+            end: 5,
+            column: 1,
         },
         Token {
             kind: TokenKind::Number,
-            lexeme: "10".to_string(),
+            source: Rc::clone(&source),
             line: 1,
             literal: Some(Literal::Number(10.0)),
+            start: 6,
+            end: 8,
+            column: 7,
         },
         Token {
             kind: TokenKind::Plus,
-            lexeme: "+".to_string(),
+            source: Rc::clone(&source),
             line: 1,
             literal: None,
+            start: 9,
+            end: 10,
+            column: 10,
         },
         Token {
             kind: TokenKind::Number,
-            lexeme: "10".to_string(),
+            source: Rc::clone(&source),
             line: 1,
             literal: Some(Literal::Number(10.0)),
+            start: 11,
+            end: 13,
+            column: 12,
         },
         Token {
             kind: TokenKind::Star,
-            lexeme: "*".to_string(),
+            source: Rc::clone(&source),
             line: 1,
             literal: None,
+            start: 14,
+            end: 15,
+            column: 15,
         },
         Token {
             kind: TokenKind::Number,
-            lexeme: "10".to_string(),
+            source: Rc::clone(&source),
             line: 1,
             literal: Some(Literal::Number(10.0)),
+            start: 16,
+            end: 18,
+            column: 17,
         },
         Token {
             kind: TokenKind::Semicolon,
-            lexeme: ";".to_string(),
+            source: Rc::clone(&source),
             line: 1,
             literal: None,
+            start: 18,
+            end: 19,
+            column: 19,
         },
         Token {
             kind: TokenKind::Eof,
-            lexeme: "".to_string(),
+            source: Rc::clone(&source),
             line: 1,
             literal: None,
+            start: 19,
+            end: 19,
+            column: 20,
         },
     ];
 
     let mut parser = Parser::new(tokens);
-    let statements = parser.parse();
+    let statements = parser.parse().unwrap();
 
     assert_eq!(statements.len(), 1);
 
-    if let Stmt::Print(expr) = &statements[0] {
+    if let Stmt::Print(expr, _) = &statements[0] {
         if let Expr::Binary(left, operator, right) = expr {
-            assert!(matches!(**left, Expr::Literal(Literal::Number(10.0))));
+            assert!(matches!(**left, Expr::Literal(Literal::Number(10.0), _)));
             assert_eq!(operator.kind, TokenKind::Plus);
             if let Expr::Binary(left_mult, operator_mult, right_mult) = &**right {
-                assert!(matches!(**left_mult, Expr::Literal(Literal::Number(10.0))));
+                assert!(matches!(
+                    **left_mult,
+                    Expr::Literal(Literal::Number(10.0), _)
+                ));
                 assert_eq!(operator_mult.kind, TokenKind::Star);
-                assert!(matches!(**right_mult, Expr::Literal(Literal::Number(10.0))));
+                assert!(matches!(
+                    **right_mult,
+                    Expr::Literal(Literal::Number(10.0), _)
+                ));
             } else {
                 panic!("Expected a binary expression for the right operand of '+'");
             }
@@ -0,0 +1,147 @@
+mod common;
+
+use common::{global, resolve, run};
+use rslox::literal::Literal;
+
+#[test]
+fn test_fields_can_be_set_and_read() {
+    let interpreter = run("class Point {}
+         var p = Point();
+         p.x = 1;
+         p.y = 2;
+         var result = p.x + p.y;")
+    .unwrap();
+
+    assert_eq!(global(&interpreter, "result"), Literal::Number(3.0));
+}
+
+#[test]
+fn test_init_binds_this_and_takes_arguments() {
+    let interpreter = run("class Counter {
+             init(start) { this.count = start; }
+             increment() {
+                 this.count = this.count + 1;
+                 return this;
+             }
+         }
+         var result = Counter(10).increment().increment().count;")
+    .unwrap();
+
+    assert_eq!(global(&interpreter, "result"), Literal::Number(12.0));
+}
+
+#[test]
+fn test_bound_method_remembers_instance() {
+    let interpreter = run("class Person {
+             init(name) { this.name = name; }
+             greet() { return \"Hi, \" + this.name; }
+         }
+         var greet = Person(\"Ada\").greet;
+         var result = greet();")
+    .unwrap();
+
+    assert_eq!(
+        global(&interpreter, "result"),
+        Literal::String("Hi, Ada".to_string())
+    );
+}
+
+#[test]
+fn test_init_returns_this_when_called_directly() {
+    let interpreter = run("class Foo {
+             init() { return; }
+         }
+         var foo = Foo();
+         var same = foo.init() == foo;")
+    .unwrap();
+
+    assert_eq!(global(&interpreter, "same"), Literal::Boolean(true));
+}
+
+#[test]
+fn test_inherited_and_super_methods() {
+    let interpreter = run("class Animal {
+             speak() { return \"...\"; }
+             name() { return \"animal\"; }
+         }
+         class Dog < Animal {
+             speak() { return super.speak() + \"woof\"; }
+         }
+         var dog = Dog();
+         var speech = dog.speak();
+         var name = dog.name();")
+    .unwrap();
+
+    assert_eq!(
+        global(&interpreter, "speech"),
+        Literal::String("...woof".to_string())
+    );
+    assert_eq!(
+        global(&interpreter, "name"),
+        Literal::String("animal".to_string())
+    );
+}
+
+#[test]
+fn test_class_and_instance_display() {
+    let interpreter = run("class Bagel {} var bagel = Bagel();").unwrap();
+
+    assert_eq!(global(&interpreter, "Bagel").to_string(), "Bagel");
+    assert_eq!(global(&interpreter, "bagel").to_string(), "Bagel instance");
+}
+
+#[test]
+fn test_undefined_property_is_runtime_error() {
+    let err = run("class Foo {} Foo().bar;").err().unwrap();
+
+    assert_eq!(err.message, "Undefined property 'bar'.");
+    assert_eq!(err.token.unwrap().lexeme(), "bar");
+}
+
+#[test]
+fn test_property_access_on_non_instance_is_runtime_error() {
+    let err = run("var x = 1; x.y;").err().unwrap();
+    assert_eq!(err.message, "Only instances have properties.");
+
+    let err = run("var x = 1; x.y = 2;").err().unwrap();
+    assert_eq!(err.message, "Only instances have fields.");
+}
+
+#[test]
+fn test_superclass_must_be_a_class() {
+    let err = run("var NotAClass = 1; class Foo < NotAClass {}")
+        .err()
+        .unwrap();
+
+    assert_eq!(err.message, "Superclass must be a class.");
+    assert_eq!(err.token.unwrap().lexeme(), "NotAClass");
+}
+
+#[test]
+fn test_initializer_arity_is_checked() {
+    let err = run("class Foo { init(a) {} } Foo();").err().unwrap();
+
+    assert_eq!(err.message, "Expected 1 arguments but got 0.");
+}
+
+#[test]
+fn test_static_class_errors() {
+    let cases = [
+        ("print this;", "Can't use 'this' outside of a class."),
+        ("super.foo();", "Can't use 'super' outside of a class."),
+        (
+            "class Foo { bar() { super.bar(); } }",
+            "Can't use 'super' in a class with no superclass.",
+        ),
+        ("class Foo < Foo {}", "A class can't inherit from itself."),
+        (
+            "class Foo { init() { return 1; } }",
+            "Can't return a value from an initializer.",
+        ),
+    ];
+
+    for (source, message) in cases {
+        let errors = resolve(source).err().unwrap();
+        assert_eq!(errors[0].message, message, "{}", source);
+    }
+}
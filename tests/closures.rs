@@ -0,0 +1,88 @@
+mod common;
+
+use common::{global, run};
+use rslox::literal::Literal;
+
+#[test]
+fn test_make_counter_keeps_private_state() {
+    let interpreter = run("fun makeCounter() {
+             var i = 0;
+             fun count() {
+                 i = i + 1;
+                 return i;
+             }
+             return count;
+         }
+         var counter = makeCounter();
+         counter();
+         counter();
+         var result = counter();")
+    .unwrap();
+
+    assert_eq!(global(&interpreter, "result"), Literal::Number(3.0));
+}
+
+#[test]
+fn test_counters_do_not_share_state() {
+    let interpreter = run("fun makeCounter() {
+             var i = 0;
+             fun count() {
+                 i = i + 1;
+                 return i;
+             }
+             return count;
+         }
+         var first = makeCounter();
+         var second = makeCounter();
+         first();
+         first();
+         var a = first();
+         var b = second();")
+    .unwrap();
+
+    assert_eq!(global(&interpreter, "a"), Literal::Number(3.0));
+    assert_eq!(global(&interpreter, "b"), Literal::Number(1.0));
+}
+
+#[test]
+fn test_closure_sees_later_mutations() {
+    let interpreter = run("var x = 1;
+         fun read() { return x; }
+         x = 2;
+         var result = read();")
+    .unwrap();
+
+    assert_eq!(global(&interpreter, "result"), Literal::Number(2.0));
+}
+
+#[test]
+fn test_closures_share_captured_variable() {
+    let interpreter = run("var get;
+         var set;
+         {
+             var value = \"before\";
+             fun getter() { return value; }
+             fun setter(v) { value = v; }
+             get = getter;
+             set = setter;
+         }
+         set(\"after\");
+         var result = get();")
+    .unwrap();
+
+    assert_eq!(
+        global(&interpreter, "result"),
+        Literal::String("after".to_string())
+    );
+}
+
+#[test]
+fn test_function_assigns_enclosing_variable() {
+    let interpreter = run("var count = 0;
+         fun increment() { count = count + 1; }
+         increment();
+         increment();")
+    .unwrap();
+
+    assert_eq!(global(&interpreter, "count"), Literal::Number(2.0));
+}
@@ -0,0 +1,50 @@
+#![allow(dead_code)]
+
+use rslox::interpreter::{Interpreter, RuntimeError};
+use rslox::literal::Literal;
+use rslox::parser::Parser;
+use rslox::resolver::{ResolveError, Resolver};
+use rslox::scanner::Scanner;
+use rslox::{token::Token, token_kind::TokenKind};
+
+pub fn run(source: &str) -> Result<Interpreter, RuntimeError> {
+    let tokens = Scanner::new(source).scan_tokens().unwrap();
+    let statements = Parser::new(tokens).parse().unwrap();
+    let mut interpreter = Interpreter::new();
+    if let Err(errors) = Resolver::new(&mut interpreter).resolve(&statements) {
+        panic!("{}", errors[0]);
+    }
+    interpreter.interpret(statements)?;
+    Ok(interpreter)
+}
+
+pub fn resolve(source: &str) -> Result<(), Vec<ResolveError>> {
+    let tokens = Scanner::new(source).scan_tokens().unwrap();
+    let statements = Parser::new(tokens).parse().unwrap();
+    Resolver::new(&mut Interpreter::new()).resolve(&statements)
+}
+
+pub fn global(interpreter: &Interpreter, name: &str) -> Literal {
+    let token = Token {
+        kind: TokenKind::Identifier,
+        source: name.into(),
+        line: 1,
+        literal: None,
+        start: 0,
+        end: name.len(),
+        column: 1,
+    };
+    let value = interpreter.globals.borrow().get(token);
+    match value {
+        Ok(value) => value,
+        Err(err) => panic!("{}", err.message),
+    }
+}
+
+pub fn number(value: f64) -> Literal {
+    Literal::Number(value)
+}
+
+pub fn string(value: &str) -> Literal {
+    Literal::String(value.to_string())
+}
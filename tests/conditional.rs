@@ -0,0 +1,112 @@
+mod common;
+
+use common::{global, run, string};
+use rslox::literal::Literal;
+
+#[test]
+fn test_ternary_picks_a_branch() {
+    let interpreter = run("var a = true ? \"yes\" : \"no\";\n\
+         var b = nil ? \"yes\" : \"no\";\n\
+         var c = 1 < 2 ? 1 + 1 : 3 * 3;")
+    .unwrap();
+
+    assert_eq!(global(&interpreter, "a"), string("yes"));
+    assert_eq!(global(&interpreter, "b"), string("no"));
+    assert_eq!(global(&interpreter, "c"), Literal::Number(2.0));
+}
+
+#[test]
+fn test_ternary_is_right_associative_and_binds_below_or() {
+    let interpreter = run(
+        "fun sign(n) { return n > 0 ? \"+\" : n < 0 ? \"-\" : \"0\"; }\n\
+         var a = sign(5);\n\
+         var b = sign(-5);\n\
+         var c = sign(0);\n\
+         var d = false or true ? \"or first\" : \"ternary first\";\n\
+         var e;\n\
+         e = true ? 1 : 2;",
+    )
+    .unwrap();
+
+    assert_eq!(global(&interpreter, "a"), string("+"));
+    assert_eq!(global(&interpreter, "b"), string("-"));
+    assert_eq!(global(&interpreter, "c"), string("0"));
+    assert_eq!(global(&interpreter, "d"), string("or first"));
+    assert_eq!(global(&interpreter, "e"), Literal::Number(1.0));
+}
+
+#[test]
+fn test_ternary_and_coalesce_are_lazy() {
+    let interpreter = run("var calls = 0;\n\
+         fun touch() { calls += 1; return \"touched\"; }\n\
+         var a = true ? \"skip\" : touch();\n\
+         var b = false ? touch() : \"skip\";\n\
+         var c = \"set\" ?? touch();\n\
+         var d = nil ?? touch();")
+    .unwrap();
+
+    assert_eq!(global(&interpreter, "calls"), Literal::Number(1.0));
+    assert_eq!(global(&interpreter, "d"), string("touched"));
+}
+
+#[test]
+fn test_coalesce_only_replaces_nil() {
+    let interpreter = run("var a = nil ?? \"default\";\n\
+         var b = false ?? \"default\";\n\
+         var c = 0 ?? \"default\";\n\
+         var d = nil ?? nil ?? 3;")
+    .unwrap();
+
+    assert_eq!(global(&interpreter, "a"), string("default"));
+    assert_eq!(global(&interpreter, "b"), Literal::Boolean(false));
+    assert_eq!(global(&interpreter, "c"), Literal::Number(0.0));
+    assert_eq!(global(&interpreter, "d"), Literal::Number(3.0));
+}
+
+#[test]
+fn test_optional_chaining() {
+    let interpreter = run("class Node {}\n\
+         var node = Node();\n\
+         node.value = 1;\n\
+         var missing = nil;\n\
+         var a = node?.value;\n\
+         var b = missing?.value;\n\
+         var c = missing?.value ?? \"none\";")
+    .unwrap();
+
+    assert_eq!(global(&interpreter, "a"), Literal::Number(1.0));
+    assert_eq!(global(&interpreter, "b"), Literal::Nil);
+    assert_eq!(global(&interpreter, "c"), string("none"));
+
+    let err = run("var n = 1; n?.value;").err().unwrap();
+    assert_eq!(err.message, "Only instances have properties.");
+}
+
+#[test]
+fn test_optional_chaining_short_circuits_the_rest_of_the_chain() {
+    let interpreter = run("class Greeter { hi() { return \"hi\"; } }\n\
+         var calls = 0;\n\
+         fun count() { calls++; return 0; }\n\
+         var o = nil;\n\
+         var a = o?.f();\n\
+         var b = o?.a.b;\n\
+         var c = o?.items[count()].name(count());\n\
+         var d = Greeter()?.hi();\n\
+         var g = Greeter();\n\
+         g.next = nil;\n\
+         var e = g?.next?.value.deeper;")
+    .unwrap();
+
+    assert_eq!(global(&interpreter, "a"), Literal::Nil);
+    assert_eq!(global(&interpreter, "b"), Literal::Nil);
+    assert_eq!(global(&interpreter, "c"), Literal::Nil);
+    assert_eq!(global(&interpreter, "calls"), Literal::Number(0.0));
+    assert_eq!(global(&interpreter, "d"), string("hi"));
+    assert_eq!(global(&interpreter, "e"), Literal::Nil);
+}
+
+#[test]
+fn test_grouping_ends_an_optional_chain() {
+    let err = run("var o = nil; (o?.a).b;").err().unwrap();
+    assert_eq!(err.message, "Only instances have properties.");
+}
@@ -0,0 +1,171 @@
+mod common;
+
+use rslox::diagnostic::{Diagnostic, Phase};
+use rslox::parser::Parser;
+use rslox::scanner::Scanner;
+use rslox::span::Span;
+
+fn scan_diagnostic(source: &str) -> Diagnostic {
+    let mut errors = Scanner::new(source).scan_tokens().unwrap_err();
+    errors.remove(0).into()
+}
+
+fn parse_diagnostic(source: &str) -> Diagnostic {
+    let tokens = Scanner::new(source).scan_tokens().unwrap();
+    let mut errors = Parser::new(tokens).parse().unwrap_err();
+    errors.remove(0).into()
+}
+
+#[test]
+fn test_renders_unterminated_string_with_help() {
+    let source = "print \"abc;";
+    let rendered = scan_diagnostic(source).render("test.lox", source, false);
+
+    assert_eq!(
+        rendered,
+        "error: Unterminated string.\n \
+         --> test.lox:1:7\n  \
+         |\n\
+         1 | print \"abc;\n  \
+         |       ^^^^^\n  \
+         = help: add a closing '\"' to end the string\n"
+    );
+}
+
+#[test]
+fn test_renders_invalid_assignment_target_with_note() {
+    let source = "var a = 1;\na + 1 = 2;";
+    let rendered = parse_diagnostic(source).render("test.lox", source, false);
+
+    assert_eq!(
+        rendered,
+        "error: Invalid assignment target.\n \
+         --> test.lox:2:7\n  \
+         |\n\
+         2 | a + 1 = 2;\n  \
+         |       ^\n  \
+         = note: only variables, fields and elements can be assigned to\n"
+    );
+}
+
+#[test]
+fn test_renders_runtime_error_under_the_operator() {
+    let source = "print 1 - \"a\";";
+    let err = common::run(source).err().unwrap();
+    let rendered = Diagnostic::from(err).render("test.lox", source, false);
+
+    assert_eq!(
+        rendered,
+        "error: Operands must be two numbers\n \
+         --> test.lox:1:9\n  \
+         |\n\
+         1 | print 1 - \"a\";\n  \
+         |         ^\n"
+    );
+}
+
+#[test]
+fn test_omits_snippet_when_span_is_outside_source() {
+    let diagnostic = Diagnostic::error(
+        Phase::Runtime,
+        "runtime-error",
+        "Oops.".to_string(),
+        Some(Span::new(40, 42, 3, 5)),
+    );
+
+    assert_eq!(
+        diagnostic.render("test.lox", "print 1;", false),
+        "error: Oops.\n --> test.lox:3:5\n"
+    );
+}
+
+#[test]
+fn test_color_wraps_severity_in_ansi_codes() {
+    let source = "print \"abc;";
+    let rendered = scan_diagnostic(source).render("test.lox", source, true);
+
+    assert!(rendered.starts_with("\x1b[1;31merror\x1b[0m: "));
+}
+
+#[test]
+fn test_json_for_scan_error() {
+    let diagnostic = scan_diagnostic("print \"abc;");
+
+    assert_eq!(
+        diagnostic.to_json("test.lox"),
+        "{\"severity\":\"error\",\"phase\":\"scan\",\"code\":\"unterminated-string\",\
+         \"message\":\"Unterminated string.\",\"file\":\"test.lox\",\"line\":1,\"column\":7,\
+         \"span\":{\"start\":6,\"end\":11},\"notes\":[],\
+         \"help\":\"add a closing '\\\"' to end the string\"}"
+    );
+}
+
+#[test]
+fn test_json_carries_phase_and_code() {
+    let diagnostic = parse_diagnostic("print 1");
+    assert_eq!(
+        (diagnostic.phase, diagnostic.code),
+        (Phase::Parse, "expected-token")
+    );
+
+    let tokens = Scanner::new("return 1;").scan_tokens().unwrap();
+    let statements = Parser::new(tokens).parse().unwrap();
+    let mut interpreter = rslox::interpreter::Interpreter::new();
+    let mut errors = rslox::resolver::Resolver::new(&mut interpreter)
+        .resolve(&statements)
+        .unwrap_err();
+    let diagnostic = Diagnostic::from(errors.remove(0));
+    assert_eq!(
+        (diagnostic.phase, diagnostic.code),
+        (Phase::Resolve, "return-from-top-level")
+    );
+
+    let err = common::run("nil();").err().unwrap();
+    let diagnostic = Diagnostic::from(err);
+    assert_eq!(
+        (diagnostic.phase, diagnostic.code),
+        (Phase::Runtime, "not-callable")
+    );
+}
+
+#[test]
+fn test_runtime_errors_have_distinct_codes() {
+    let cases = [
+        ("print x;", "undefined-variable"),
+        ("class A {} A().x;", "undefined-property"),
+        ("-\"a\";", "invalid-operand"),
+        ("1 < \"a\";", "invalid-operand"),
+        ("var n = 1; n.x;", "not-an-instance"),
+        ("fun f(a) {} f();", "arity-mismatch"),
+        ("var A = 1; class B < A {}", "superclass-not-class"),
+        ("fun f() { f(); } f();", "stack-overflow"),
+        ("1[0];", "not-indexable"),
+        ("[1][0.5];", "invalid-index"),
+        ("[1][1];", "index-out-of-range"),
+        ("var m = {}; m[[]] = 1;", "unhashable-key"),
+        ("var m = {}; m[0 / 0];", "unhashable-key"),
+        ("var m = {}; m[\"a\"];", "undefined-key"),
+    ];
+
+    for (source, code) in cases {
+        let err = common::run(source).err().unwrap();
+        assert_eq!(Diagnostic::from(err).code, code, "{}", source);
+    }
+}
+
+#[test]
+fn test_json_escapes_strings_and_handles_missing_span() {
+    let diagnostic = Diagnostic::error(
+        Phase::Runtime,
+        "runtime-error",
+        "Bad \"name\"\n\tthere.".to_string(),
+        None,
+    );
+
+    assert_eq!(
+        diagnostic.to_json("dir\\test.lox"),
+        "{\"severity\":\"error\",\"phase\":\"runtime\",\"code\":\"runtime-error\",\
+         \"message\":\"Bad \\\"name\\\"\\n\\tthere.\",\"file\":\"dir\\\\test.lox\",\
+         \"line\":null,\"column\":null,\"span\":null,\"notes\":[],\"help\":null}"
+    );
+}
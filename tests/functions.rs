@@ -0,0 +1,155 @@
+mod common;
+
+use common::{global, run};
+use rslox::diagnostic::ErrorFormat;
+use rslox::interpreter::Interpreter;
+use rslox::literal::Literal;
+use rslox::parser::{ParseErrorKind, Parser};
+use rslox::run_repl;
+use rslox::scanner::Scanner;
+
+#[test]
+fn test_function_call_returns_value() {
+    let interpreter = run("fun add(a, b) { return a + b; }
+         var result = add(1, 2);")
+    .unwrap();
+
+    assert_eq!(global(&interpreter, "result"), Literal::Number(3.0));
+}
+
+#[test]
+fn test_function_without_return_yields_nil() {
+    let interpreter = run("fun noop() {}
+         var result = noop();")
+    .unwrap();
+
+    assert_eq!(global(&interpreter, "result"), Literal::Nil);
+}
+
+#[test]
+fn test_return_unwinds_nested_statements() {
+    let interpreter = run("fun first(limit) {
+             var i = 0;
+             while (true) {
+                 if (i == limit) { return i; }
+                 i = i + 1;
+             }
+         }
+         var result = first(3);")
+    .unwrap();
+
+    assert_eq!(global(&interpreter, "result"), Literal::Number(3.0));
+}
+
+#[test]
+fn test_recursive_function() {
+    let interpreter = run("fun fib(n) {
+             if (n < 2) return n;
+             return fib(n - 2) + fib(n - 1);
+         }
+         var result = fib(10);")
+    .unwrap();
+
+    assert_eq!(global(&interpreter, "result"), Literal::Number(55.0));
+}
+
+#[test]
+fn test_function_prints_as_fn() {
+    let interpreter = run("fun greet() {}").unwrap();
+
+    assert_eq!(global(&interpreter, "greet").to_string(), "<fn greet>");
+}
+
+#[test]
+fn test_arity_mismatch_is_runtime_error() {
+    let err = run("fun add(a, b) { return a + b; } add(1);")
+        .err()
+        .unwrap();
+
+    assert_eq!(err.message, "Expected 2 arguments but got 1.");
+    assert_eq!(err.token.unwrap().lexeme(), ")");
+}
+
+#[test]
+fn test_calling_non_callable_is_runtime_error() {
+    let err = run("var x = 1; x();").err().unwrap();
+
+    assert_eq!(err.message, "Can only call functions and classes.");
+}
+
+#[test]
+fn test_runaway_recursion_is_runtime_error() {
+    // This runs on the test harness's thread, with the default stack size.
+    let interpreter = run("fun f(n) { if (n > 0) return f(n - 1) + 1; return 0; }
+         var result = f(50);")
+    .unwrap();
+    assert_eq!(global(&interpreter, "result"), Literal::Number(50.0));
+
+    let err = run("fun f() { f(); } f();").err().unwrap();
+    assert_eq!(err.message, "Stack overflow.");
+    assert_eq!(err.token.unwrap().lexeme(), ")");
+
+    let err = run("class A { init() { A(); } } A();").err().unwrap();
+    assert_eq!(err.message, "Stack overflow.");
+}
+
+#[test]
+fn test_calls_work_again_after_a_stack_overflow() {
+    let mut interpreter = Interpreter::new();
+    let source = "fun f() { f(); }";
+    assert_eq!(
+        run_repl(&mut interpreter, source, ErrorFormat::Human),
+        Ok(None)
+    );
+    assert_eq!(
+        run_repl(&mut interpreter, "f();", ErrorFormat::Human),
+        Err(70)
+    );
+    assert_eq!(
+        run_repl(
+            &mut interpreter,
+            "fun g(n) { return n; } g(1)",
+            ErrorFormat::Human
+        ),
+        Ok(Some(Literal::Number(1.0)))
+    );
+}
+
+#[test]
+fn test_lambdas_as_arguments_values_and_results() {
+    let interpreter = run("fun apply(f, x) { return f(x); }
+         var doubled = apply(fun (n) { return n * 2; }, 21);
+         var add = fun (a, b) { return a + b; };
+         var sum = add(1, 2);
+         fun adder(n) { return fun (x) { return x + n; }; }
+         var plusTen = adder(10)(5);
+         var printed = \"${add}\";")
+    .unwrap();
+
+    assert_eq!(global(&interpreter, "doubled"), Literal::Number(42.0));
+    assert_eq!(global(&interpreter, "sum"), Literal::Number(3.0));
+    assert_eq!(global(&interpreter, "plusTen"), Literal::Number(15.0));
+    assert_eq!(
+        global(&interpreter, "printed"),
+        Literal::String("<fn>".to_string())
+    );
+}
+
+#[test]
+fn test_lambda_can_start_an_expression_statement() {
+    let interpreter = run("var called = false;
+         fun () { called = true; }();")
+    .unwrap();
+
+    assert_eq!(global(&interpreter, "called"), Literal::Boolean(true));
+}
+
+#[test]
+fn test_lambdas_share_the_parameter_limit() {
+    let params: Vec<_> = (0..256).map(|i| format!("p{}", i)).collect();
+    let source = format!("var f = fun ({}) {{}};", params.join(", "));
+    let tokens = Scanner::new(source).scan_tokens().unwrap();
+    let errors = Parser::new(tokens).parse().unwrap_err();
+
+    assert_eq!(errors[0].kind, ParseErrorKind::TooManyParameters);
+}
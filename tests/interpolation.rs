@@ -0,0 +1,102 @@
+mod common;
+
+use common::{global, run, string};
+use rslox::parser::{ParseErrorKind, Parser};
+use rslox::scanner::{ScanErrorKind, Scanner};
+use rslox::token_kind::TokenKind;
+
+#[test]
+fn test_scanner_splits_interpolated_strings() {
+    let tokens = Scanner::new("\"a ${b} c ${ {} } d\"")
+        .scan_tokens()
+        .unwrap();
+
+    let kinds: Vec<_> = tokens.iter().map(|token| token.kind).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            TokenKind::Interpolation,
+            TokenKind::Identifier,
+            TokenKind::Interpolation,
+            TokenKind::LeftBrace,
+            TokenKind::RightBrace,
+            TokenKind::String,
+            TokenKind::Eof,
+        ]
+    );
+    assert_eq!(tokens[0].lexeme(), "\"a ${");
+    assert_eq!(tokens[2].literal, Some(string(" c ")));
+    assert_eq!(tokens[5].lexeme(), "} d\"");
+}
+
+#[test]
+fn test_interpolation_stringifies_like_print() {
+    let interpreter = run("var name = \"Ada\";\n\
+         var age = 36;\n\
+         var greeting = \"Hello ${name}, you are ${age + 1}!\";\n\
+         var mixed = \"${nil} ${true} ${1.5} ${1 < 2}\";\n\
+         fun f() {}\n\
+         var fn = \"${f}\";")
+    .unwrap();
+
+    assert_eq!(
+        global(&interpreter, "greeting"),
+        string("Hello Ada, you are 37!")
+    );
+    assert_eq!(global(&interpreter, "mixed"), string("nil true 1.5 true"));
+    assert_eq!(global(&interpreter, "fn"), string("<fn f>"));
+}
+
+#[test]
+fn test_interpolations_nest() {
+    let interpreter = run("var x = 2;\nvar s = \"outer ${\"inner ${x * 2}\"} done\";").unwrap();
+
+    assert_eq!(global(&interpreter, "s"), string("outer inner 4 done"));
+}
+
+#[test]
+fn test_escaped_dollar_is_not_interpolated() {
+    let interpreter = run("var s = \"cost: \\${x}\";").unwrap();
+
+    assert_eq!(global(&interpreter, "s"), string("cost: ${x}"));
+}
+
+#[test]
+fn test_unclosed_interpolation_is_an_error() {
+    let errors = Scanner::new("print \"a ${b;").scan_tokens().unwrap_err();
+
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].kind, ScanErrorKind::UnterminatedString);
+    assert_eq!((errors[0].line, errors[0].column), (1, 7));
+}
+
+#[test]
+fn test_missing_brace_after_interpolated_expression() {
+    let tokens = Scanner::new("print \"a ${b c}\";").scan_tokens().unwrap();
+    let errors = Parser::new(tokens).parse().unwrap_err();
+
+    assert_eq!(
+        errors[0].message,
+        "Expect '}' after interpolated expression."
+    );
+    assert_eq!(errors[0].token.lexeme(), "c");
+}
+
+#[test]
+fn test_empty_interpolation_is_an_error() {
+    for (source, column) in [
+        ("print \"${}\" \"x\";", 8),
+        ("print \"a${}\";", 9),
+        ("print \"a${x}b${ }\";", 14),
+    ] {
+        let tokens = Scanner::new(source).scan_tokens().unwrap();
+        let errors = Parser::new(tokens).parse().unwrap_err();
+
+        assert_eq!(errors.len(), 1, "{}", source);
+        assert_eq!(errors[0].kind, ParseErrorKind::ExpectedExpression);
+        assert_eq!(errors[0].message, "Expect expression inside '${}'.");
+        assert!(errors[0].token.lexeme().starts_with("${"), "{}", source);
+        assert!(errors[0].token.lexeme().ends_with('}'), "{}", source);
+        assert_eq!(errors[0].token.column, column, "{}", source);
+    }
+}
@@ -0,0 +1,106 @@
+mod common;
+
+use common::{global, run, string};
+use rslox::literal::Literal;
+use rslox::repl::is_complete;
+
+#[test]
+fn test_list_literals_and_indexing() {
+    let interpreter = run("var xs = [1, \"two\", nil, [3],];\n\
+         var a = xs[0];\n\
+         var b = xs[1];\n\
+         var c = xs[3][0];\n\
+         var d = [];\n\
+         var e = [10, 20, 30][1 + 1];")
+    .unwrap();
+
+    assert_eq!(global(&interpreter, "a"), Literal::Number(1.0));
+    assert_eq!(global(&interpreter, "b"), string("two"));
+    assert_eq!(global(&interpreter, "c"), Literal::Number(3.0));
+    assert_eq!(global(&interpreter, "e"), Literal::Number(30.0));
+    assert_eq!(global(&interpreter, "d").to_string(), "[]");
+}
+
+#[test]
+fn test_element_assignment_and_updates() {
+    let interpreter = run("var xs = [1, 2, 3];\n\
+         var a = (xs[0] = 10);\n\
+         xs[1] += 5;\n\
+         var b = xs[2]++;\n\
+         var i = 0;\n\
+         var ys = [0, 0];\n\
+         ys[i++] = i;")
+    .unwrap();
+
+    assert_eq!(global(&interpreter, "a"), Literal::Number(10.0));
+    assert_eq!(global(&interpreter, "b"), Literal::Number(3.0));
+    assert_eq!(global(&interpreter, "xs").to_string(), "[10, 7, 4]");
+    assert_eq!(global(&interpreter, "ys").to_string(), "[1, 0]");
+}
+
+#[test]
+fn test_lists_are_shared_by_reference() {
+    let interpreter = run("var xs = [1, 2];\n\
+         var ys = xs;\n\
+         ys[0] = \"changed\";\n\
+         fun set(list) { list[1] = true; }\n\
+         set(xs);\n\
+         var same = xs == ys;\n\
+         var equal_contents = [1] == [1];")
+    .unwrap();
+
+    assert_eq!(
+        global(&interpreter, "xs").to_string(),
+        "[\"changed\", true]"
+    );
+    assert_eq!(global(&interpreter, "same"), Literal::Boolean(true));
+    assert_eq!(
+        global(&interpreter, "equal_contents"),
+        Literal::Boolean(false)
+    );
+}
+
+#[test]
+fn test_lists_print_readably() {
+    let interpreter = run("var xs = [1, 2.5, \"a\\\"b\", nil, false, [[]]];\n\
+         var self = [1];\n\
+         self[0] = self;")
+    .unwrap();
+
+    assert_eq!(
+        global(&interpreter, "xs").to_string(),
+        "[1, 2.5, \"a\\\"b\", nil, false, [[]]]"
+    );
+    assert_eq!(global(&interpreter, "self").to_string(), "[[...]]");
+}
+
+#[test]
+fn test_bad_indexes_are_runtime_errors() {
+    let cases = [
+        ("[1, 2][2];", "List index 2 out of range for length 2."),
+        ("[1, 2][-1];", "List index -1 out of range for length 2."),
+        (
+            "var xs = []; xs[0] = 1;",
+            "List index 0 out of range for length 0.",
+        ),
+        ("[1, 2][0.5];", "List index must be an integer."),
+        ("[1, 2][\"0\"];", "List index must be a number."),
+        (
+            "var s = \"abc\"; s[0];",
+            "Only lists and maps can be indexed.",
+        ),
+        ("nil[0] = 1;", "Only lists and maps can be indexed."),
+    ];
+
+    for (source, message) in cases {
+        let err = run(source).err().unwrap();
+        assert_eq!(err.message, message, "{}", source);
+        assert_eq!(err.token.unwrap().lexeme(), "]", "{}", source);
+    }
+}
+
+#[test]
+fn test_repl_waits_for_closing_bracket() {
+    assert!(!is_complete("var xs = [1,"));
+    assert!(is_complete("var xs = [1,\n2];"));
+}
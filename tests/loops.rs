@@ -0,0 +1,84 @@
+mod common;
+
+use common::{global, run};
+use rslox::literal::Literal;
+use rslox::parser::{ParseErrorKind, Parser};
+use rslox::scanner::Scanner;
+
+#[test]
+fn test_break_leaves_the_innermost_loop() {
+    let interpreter = run("var total = 0;\n\
+         for (var i = 0; i < 3; i = i + 1) {\n\
+           var j = 0;\n\
+           while (true) {\n\
+             if (j == 2) break;\n\
+             total = total + 1;\n\
+             j = j + 1;\n\
+           }\n\
+         }")
+    .unwrap();
+
+    assert_eq!(global(&interpreter, "total"), Literal::Number(6.0));
+}
+
+#[test]
+fn test_continue_in_for_still_runs_the_increment() {
+    let interpreter = run("var sum = 0;\n\
+         var iterations = 0;\n\
+         for (var i = 0; i < 10; i = i + 1) {\n\
+           iterations = iterations + 1;\n\
+           if (i == 2 or i == 5) continue;\n\
+           sum = sum + i;\n\
+         }")
+    .unwrap();
+
+    assert_eq!(global(&interpreter, "iterations"), Literal::Number(10.0));
+    assert_eq!(global(&interpreter, "sum"), Literal::Number(38.0));
+}
+
+#[test]
+fn test_continue_in_while_rechecks_the_condition() {
+    let interpreter = run("var i = 0;\n\
+         var odd = 0;\n\
+         while (i < 5) {\n\
+           i = i + 1;\n\
+           if (i == 2 or i == 4) { continue; }\n\
+           odd = odd + i;\n\
+         }")
+    .unwrap();
+
+    assert_eq!(global(&interpreter, "odd"), Literal::Number(9.0));
+}
+
+#[test]
+fn test_break_unwinds_block_scopes() {
+    let interpreter = run("var a = \"global\";\n\
+         var seen;\n\
+         while (true) { var a = \"inner\"; { break; } }\n\
+         seen = a;")
+    .unwrap();
+
+    assert_eq!(
+        global(&interpreter, "seen"),
+        Literal::String("global".to_string())
+    );
+}
+
+#[test]
+fn test_break_and_continue_outside_a_loop_are_parse_errors() {
+    for (source, keyword) in [
+        ("break;", "break"),
+        ("if (true) continue;", "continue"),
+        ("while (true) { fun f() { break; } }", "break"),
+    ] {
+        let tokens = Scanner::new(source).scan_tokens().unwrap();
+        let errors = Parser::new(tokens).parse().unwrap_err();
+
+        assert_eq!(errors.len(), 1, "{}", source);
+        assert_eq!(errors[0].kind, ParseErrorKind::OutsideLoop);
+        assert_eq!(
+            errors[0].message,
+            format!("Can't use '{}' outside of a loop.", keyword)
+        );
+    }
+}
@@ -0,0 +1,133 @@
+mod common;
+
+use common::{global, run};
+use rslox::literal::Literal;
+
+#[test]
+fn test_map_literals_and_lookup() {
+    let interpreter = run("var m = {\"a\": 1, 2: \"two\", true: [3], nil: {},};\n\
+         var a = m[\"a\"];\n\
+         var b = m[1 + 1];\n\
+         var c = m[true][0];\n\
+         var d = m[nil];\n\
+         var e = {\"x\": 1, \"x\": 2}[\"x\"];")
+    .unwrap();
+
+    assert_eq!(global(&interpreter, "a"), Literal::Number(1.0));
+    assert_eq!(
+        global(&interpreter, "b"),
+        Literal::String("two".to_string())
+    );
+    assert_eq!(global(&interpreter, "c"), Literal::Number(3.0));
+    assert_eq!(global(&interpreter, "d").to_string(), "{}");
+    assert_eq!(global(&interpreter, "e"), Literal::Number(2.0));
+}
+
+#[test]
+fn test_entry_assignment_and_updates() {
+    let interpreter = run("var m = {};\n\
+         m[\"count\"] = 1;\n\
+         m[\"count\"] += 2;\n\
+         var old = m[\"count\"]++;\n\
+         var set = (m[false] = \"no\");")
+    .unwrap();
+
+    assert_eq!(global(&interpreter, "old"), Literal::Number(3.0));
+    assert_eq!(
+        global(&interpreter, "set"),
+        Literal::String("no".to_string())
+    );
+    assert_eq!(
+        global(&interpreter, "m").to_string(),
+        "{\"count\": 4, false: \"no\"}"
+    );
+}
+
+#[test]
+fn test_number_keys_hash_by_value() {
+    let interpreter = run("var m = {-0: \"zero\", 1: \"one\"};\n\
+         var a = m[0];\n\
+         var b = m[1.0];\n\
+         m[0] = \"again\";")
+    .unwrap();
+
+    assert_eq!(
+        global(&interpreter, "a"),
+        Literal::String("zero".to_string())
+    );
+    assert_eq!(
+        global(&interpreter, "b"),
+        Literal::String("one".to_string())
+    );
+    assert_eq!(
+        global(&interpreter, "m").to_string(),
+        "{0: \"again\", 1: \"one\"}"
+    );
+}
+
+#[test]
+fn test_maps_are_shared_by_reference() {
+    let interpreter = run("var m = {\"a\": 1};\n\
+         var alias = m;\n\
+         alias[\"b\"] = 2;\n\
+         var same = m == alias;\n\
+         var equal_contents = {} == {};\n\
+         var self = {};\n\
+         self[\"self\"] = self;")
+    .unwrap();
+
+    assert_eq!(
+        global(&interpreter, "m").to_string(),
+        "{\"a\": 1, \"b\": 2}"
+    );
+    assert_eq!(global(&interpreter, "same"), Literal::Boolean(true));
+    assert_eq!(
+        global(&interpreter, "equal_contents"),
+        Literal::Boolean(false)
+    );
+    assert_eq!(
+        global(&interpreter, "self").to_string(),
+        "{\"self\": {...}}"
+    );
+}
+
+#[test]
+fn test_braces_at_statement_start_are_still_blocks() {
+    let interpreter = run("var a = 1;\n{ var a = 2; }\nvar m = { \"a\": a };").unwrap();
+
+    assert_eq!(global(&interpreter, "m").to_string(), "{\"a\": 1}");
+}
+
+#[test]
+fn test_bad_keys_are_runtime_errors() {
+    let cases = [
+        (
+            "var m = {}; m[0 / 0];",
+            "NaN can't be used as a map key.",
+            "]",
+        ),
+        (
+            "var m = {0 / 0: 1};",
+            "NaN can't be used as a map key.",
+            ":",
+        ),
+        (
+            "var m = {}; m[[1]] = 1;",
+            "Only numbers, strings, booleans and nil can be map keys.",
+            "]",
+        ),
+        (
+            "fun f() {} var m = {f: 1};",
+            "Only numbers, strings, booleans and nil can be map keys.",
+            ":",
+        ),
+        ("var m = {\"a\": 1}; m[\"b\"];", "Undefined key \"b\".", "]"),
+        ("var m = {}; m[2] += 1;", "Undefined key 2.", "]"),
+    ];
+
+    for (source, message, lexeme) in cases {
+        let err = run(source).err().unwrap();
+        assert_eq!(err.message, message, "{}", source);
+        assert_eq!(err.token.unwrap().lexeme(), lexeme, "{}", source);
+    }
+}
@@ -0,0 +1,144 @@
+mod common;
+
+use common::{global, number, run};
+use rslox::literal::Literal;
+use rslox::parser::{ParseErrorKind, Parser};
+use rslox::scanner::Scanner;
+
+#[test]
+fn test_modulo_and_exponent() {
+    let interpreter = run("var a = 7 % 3;\n\
+         var b = -7 % 3;\n\
+         var c = 2 ** 3 ** 2;\n\
+         var d = -2 ** 2;\n\
+         var e = 2 ** -1;\n\
+         var f = 1 + 2 * 3 ** 2 % 5;")
+    .unwrap();
+
+    assert_eq!(global(&interpreter, "a"), number(1.0));
+    assert_eq!(global(&interpreter, "b"), number(-1.0));
+    assert_eq!(global(&interpreter, "c"), number(512.0));
+    assert_eq!(global(&interpreter, "d"), number(-4.0));
+    assert_eq!(global(&interpreter, "e"), number(0.5));
+    assert_eq!(global(&interpreter, "f"), number(4.0));
+}
+
+#[test]
+fn test_binary_operators_respect_precedence() {
+    let interpreter = run("var a = false or true and true;\n\
+         var b = 2 == 1 + 1;\n\
+         var c = 1 < 1 + 1;\n\
+         var d = 1 + 1 != 2 * 1;")
+    .unwrap();
+
+    assert_eq!(global(&interpreter, "a"), Literal::Boolean(true));
+    assert_eq!(global(&interpreter, "b"), Literal::Boolean(true));
+    assert_eq!(global(&interpreter, "c"), Literal::Boolean(true));
+    assert_eq!(global(&interpreter, "d"), Literal::Boolean(false));
+}
+
+#[test]
+fn test_compound_assignment_on_variables() {
+    let interpreter = run("var a = 10;\n\
+         a += 5; a -= 3; a *= 4; a /= 6; a %= 5;\n\
+         var s = \"ab\";\n\
+         s += \"cd\";\n\
+         var result = (a += 10);")
+    .unwrap();
+
+    assert_eq!(global(&interpreter, "a"), number(13.0));
+    assert_eq!(
+        global(&interpreter, "s"),
+        Literal::String("abcd".to_string())
+    );
+    assert_eq!(global(&interpreter, "result"), number(13.0));
+}
+
+#[test]
+fn test_compound_assignment_on_fields_evaluates_object_once() {
+    let interpreter = run("class Box {}\n\
+         var box = Box();\n\
+         box.count = 1;\n\
+         var calls = 0;\n\
+         fun get() { calls += 1; return box; }\n\
+         get().count += 10;\n\
+         get().count *= 2;\n\
+         var count = box.count;")
+    .unwrap();
+
+    assert_eq!(global(&interpreter, "count"), number(22.0));
+    assert_eq!(global(&interpreter, "calls"), number(2.0));
+}
+
+#[test]
+fn test_prefix_and_postfix_increments() {
+    let interpreter = run("var i = 5;\n\
+         var a = i++;\n\
+         var b = ++i;\n\
+         var c = i--;\n\
+         var d = --i;\n\
+         class Counter {}\n\
+         var counter = Counter();\n\
+         counter.n = 0;\n\
+         counter.n++;\n\
+         ++counter.n;\n\
+         var n = counter.n;\n\
+         var total = 0;\n\
+         for (var j = 0; j < 4; j++) total += j;")
+    .unwrap();
+
+    assert_eq!(global(&interpreter, "a"), number(5.0));
+    assert_eq!(global(&interpreter, "b"), number(7.0));
+    assert_eq!(global(&interpreter, "c"), number(7.0));
+    assert_eq!(global(&interpreter, "d"), number(5.0));
+    assert_eq!(global(&interpreter, "i"), number(5.0));
+    assert_eq!(global(&interpreter, "n"), number(2.0));
+    assert_eq!(global(&interpreter, "total"), number(6.0));
+}
+
+#[test]
+fn test_increments_bind_tighter_than_exponent() {
+    let interpreter = run("var a = 2;\n\
+         var b = ++a ** 2;\n\
+         var c = a++ ** 2;\n\
+         var d = 2 ** ++a;")
+    .unwrap();
+
+    assert_eq!(global(&interpreter, "b"), number(9.0));
+    assert_eq!(global(&interpreter, "c"), number(9.0));
+    assert_eq!(global(&interpreter, "d"), number(32.0));
+    assert_eq!(global(&interpreter, "a"), number(5.0));
+}
+
+#[test]
+fn test_updates_reuse_numeric_type_checks() {
+    let err = run("var a = \"x\"; a -= 1;").err().unwrap();
+    assert_eq!(err.message, "Operands must be two numbers");
+    assert_eq!(err.token.unwrap().lexeme(), "-=");
+
+    let err = run("var b = nil; b++;").err().unwrap();
+    assert_eq!(err.message, "Operand must be a number.");
+    assert_eq!(err.token.unwrap().lexeme(), "++");
+
+    let err = run("var s = \"a\"; --s;").err().unwrap();
+    assert_eq!(err.message, "Operand must be a number.");
+    assert_eq!(err.token.unwrap().lexeme(), "--");
+
+    let interpreter = run("var s = \"a\"; s += 1;").unwrap();
+    assert_eq!(global(&interpreter, "s"), Literal::String("a1".to_string()));
+}
+
+#[test]
+fn test_updates_need_a_variable_or_field() {
+    for source in ["1 += 2;", "(a) -= 1;", "++1;", "f()--;"] {
+        let tokens = Scanner::new(source).scan_tokens().unwrap();
+        let errors = Parser::new(tokens).parse().unwrap_err();
+
+        assert_eq!(
+            errors[0].kind,
+            ParseErrorKind::InvalidAssignmentTarget,
+            "{}",
+            source
+        );
+    }
+}
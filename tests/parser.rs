@@ -0,0 +1,81 @@
+use rslox::parser::{ParseError, ParseErrorKind, Parser};
+use rslox::scanner::Scanner;
+use rslox::token_kind::TokenKind;
+
+fn parse_errors(source: &str) -> Vec<ParseError> {
+    let tokens = Scanner::new(source).scan_tokens().unwrap();
+    Parser::new(tokens).parse().unwrap_err()
+}
+
+#[test]
+fn test_missing_semicolon_is_reported() {
+    let errors = parse_errors("print 1");
+
+    assert_eq!(errors.len(), 1);
+    assert_eq!(
+        errors[0].kind,
+        ParseErrorKind::ExpectedToken(TokenKind::Semicolon)
+    );
+    assert_eq!(errors[0].message, "Expect ';' after value.");
+    assert_eq!(errors[0].token.kind, TokenKind::Eof);
+    assert_eq!(
+        errors[0].to_string(),
+        "[line 1] Error at end: Expect ';' after value."
+    );
+}
+
+#[test]
+fn test_errors_are_collected_after_synchronizing() {
+    let errors = parse_errors("var = 1;\nprint 2;\n1 = 2;\nprint );");
+
+    let kinds: Vec<_> = errors.iter().map(|err| err.kind).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            ParseErrorKind::ExpectedToken(TokenKind::Identifier),
+            ParseErrorKind::InvalidAssignmentTarget,
+            ParseErrorKind::ExpectedExpression,
+        ]
+    );
+    assert_eq!(errors[1].token.line, 3);
+    assert_eq!(
+        errors[2].to_string(),
+        "[line 4] Error at ')': Expect expression."
+    );
+}
+
+#[test]
+fn test_errors_inside_blocks_are_collected() {
+    let errors = parse_errors("fun f() { print ; }\nclass A { 1 }");
+
+    assert_eq!(errors.len(), 2);
+    assert_eq!(errors[0].kind, ParseErrorKind::ExpectedExpression);
+    assert_eq!(
+        errors[1].kind,
+        ParseErrorKind::ExpectedToken(TokenKind::Identifier)
+    );
+}
+
+#[test]
+fn test_too_many_arguments() {
+    let arguments = vec!["1"; 256].join(", ");
+    let errors = parse_errors(&format!("f({});", arguments));
+
+    assert_eq!(errors[0].kind, ParseErrorKind::TooManyArguments);
+}
+
+#[test]
+fn test_parses_directly_from_the_scanner() {
+    let mut scanner = Scanner::new("var a = 1;\nprint a + 2;");
+    let statements = Parser::new(&mut scanner).parse().unwrap();
+
+    assert_eq!(statements.len(), 2);
+    assert!(scanner.errors().is_empty());
+}
+
+#[test]
+fn test_empty_program_parses() {
+    let statements = Parser::new(Scanner::new("")).parse().unwrap();
+
+    assert!(statements.is_empty());
+}
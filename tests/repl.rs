@@ -0,0 +1,243 @@
+mod common;
+
+use common::global;
+use rslox::diagnostic::ErrorFormat;
+use rslox::interpreter::Interpreter;
+use rslox::literal::Literal;
+use rslox::repl::{is_complete, Repl};
+use rslox::{run, run_repl};
+
+#[test]
+fn test_definitions_persist_between_runs() {
+    let mut interpreter = Interpreter::new();
+
+    run(
+        &mut interpreter,
+        "test.lox",
+        "var a = 1;",
+        ErrorFormat::Human,
+    )
+    .unwrap();
+    run(
+        &mut interpreter,
+        "test.lox",
+        "fun double(n) { return n * 2; }",
+        ErrorFormat::Human,
+    )
+    .unwrap();
+    run(
+        &mut interpreter,
+        "test.lox",
+        "class Box { init(v) { this.v = v; } }",
+        ErrorFormat::Human,
+    )
+    .unwrap();
+    run(
+        &mut interpreter,
+        "test.lox",
+        "var result = double(Box(a).v);",
+        ErrorFormat::Human,
+    )
+    .unwrap();
+
+    assert_eq!(global(&interpreter, "result"), Literal::Number(2.0));
+}
+
+#[test]
+fn test_closures_from_earlier_runs_keep_their_scope() {
+    let mut interpreter = Interpreter::new();
+
+    run(
+        &mut interpreter,
+        "test.lox",
+        "fun makeCounter() { var i = 0; fun count() { i = i + 1; return i; } return count; }",
+        ErrorFormat::Human,
+    )
+    .unwrap();
+    run(
+        &mut interpreter,
+        "test.lox",
+        "var counter = makeCounter();",
+        ErrorFormat::Human,
+    )
+    .unwrap();
+    run(
+        &mut interpreter,
+        "test.lox",
+        "counter();",
+        ErrorFormat::Human,
+    )
+    .unwrap();
+    run(
+        &mut interpreter,
+        "test.lox",
+        "var result = counter();",
+        ErrorFormat::Human,
+    )
+    .unwrap();
+
+    assert_eq!(global(&interpreter, "result"), Literal::Number(2.0));
+}
+
+#[test]
+fn test_session_survives_errors() {
+    let mut interpreter = Interpreter::new();
+
+    run(
+        &mut interpreter,
+        "test.lox",
+        "var a = 1;",
+        ErrorFormat::Human,
+    )
+    .unwrap();
+    assert_eq!(
+        run(
+            &mut interpreter,
+            "test.lox",
+            "a = a + nil;",
+            ErrorFormat::Human
+        ),
+        Err(70)
+    );
+    assert_eq!(
+        run(
+            &mut interpreter,
+            "test.lox",
+            "return a;",
+            ErrorFormat::Human
+        ),
+        Err(65)
+    );
+    run(
+        &mut interpreter,
+        "test.lox",
+        "a = a + 1;",
+        ErrorFormat::Human,
+    )
+    .unwrap();
+
+    assert_eq!(global(&interpreter, "a"), Literal::Number(2.0));
+}
+
+#[test]
+fn test_bare_expression_is_echoed() {
+    let mut interpreter = Interpreter::new();
+
+    assert_eq!(
+        run_repl(&mut interpreter, "1 + 2", ErrorFormat::Human),
+        Ok(Some(Literal::Number(3.0)))
+    );
+    assert_eq!(
+        run_repl(&mut interpreter, "1 + 2;", ErrorFormat::Human),
+        Ok(Some(Literal::Number(3.0)))
+    );
+}
+
+#[test]
+fn test_trailing_expression_runs_after_statements() {
+    let mut interpreter = Interpreter::new();
+
+    assert_eq!(
+        run_repl(
+            &mut interpreter,
+            "var a = 1; a = a + 1; a * 10",
+            ErrorFormat::Human
+        ),
+        Ok(Some(Literal::Number(20.0)))
+    );
+}
+
+#[test]
+fn test_statements_are_not_echoed() {
+    let mut interpreter = Interpreter::new();
+
+    assert_eq!(
+        run_repl(&mut interpreter, "var a = 1;", ErrorFormat::Human),
+        Ok(None)
+    );
+    assert_eq!(
+        run_repl(&mut interpreter, "{ a; }", ErrorFormat::Human),
+        Ok(None)
+    );
+}
+
+#[test]
+fn test_files_still_require_semicolons() {
+    let mut interpreter = Interpreter::new();
+
+    assert_eq!(
+        run(
+            &mut interpreter,
+            "test.lox",
+            "var a = 1; a = 2",
+            ErrorFormat::Human
+        ),
+        Err(65)
+    );
+    assert_eq!(
+        run_repl(&mut interpreter, "var a = 1; a = 2", ErrorFormat::Human),
+        Ok(Some(Literal::Number(2.0)))
+    );
+}
+
+#[test]
+fn test_statement_bodies_still_require_semicolons() {
+    let mut interpreter = Interpreter::new();
+    run_repl(&mut interpreter, "var x = 1;", ErrorFormat::Human).unwrap();
+
+    for source in [
+        "if (true) x = 2",
+        "if (false) x = 2; else x = 3",
+        "while (x > 5) x = x - 1",
+        "for (;x > 5;) x = x - 1",
+    ] {
+        assert_eq!(
+            run_repl(&mut interpreter, source, ErrorFormat::Human),
+            Err(65),
+            "{}",
+            source
+        );
+    }
+    assert_eq!(global(&interpreter, "x"), Literal::Number(1.0));
+}
+
+#[test]
+fn test_input_is_incomplete_while_delimiters_are_open() {
+    assert!(!is_complete("fun add(a, b) {"));
+    assert!(!is_complete("print add(1,"));
+    assert!(!is_complete("var s = \"open"));
+    assert!(!is_complete("{ // }"));
+    assert!(!is_complete("/* still /* open */"));
+    assert!(is_complete("var s = \"{(\";"));
+    assert!(is_complete("fun add(a, b) {\n  return a + b;\n}"));
+}
+
+#[test]
+fn test_tokens_command_lists_scanned_tokens() {
+    let output = Repl::new(ErrorFormat::Human).command(":tokens 1 + x");
+
+    assert_eq!(
+        output,
+        "   1 Number '1'\n   1 Plus '+'\n   1 Identifier 'x'\n   1 Eof ''"
+    );
+}
+
+#[test]
+fn test_load_env_and_reset_commands() {
+    let mut repl = Repl::new(ErrorFormat::Human);
+
+    assert_eq!(repl.command(":load examples/variables.lox"), "");
+    assert_eq!(repl.command(":env"), "test = 9\ntest2 = oi");
+    assert_eq!(global(repl.interpreter(), "test"), Literal::Number(9.0));
+
+    repl.command(":reset");
+    assert_eq!(repl.command(":env"), "");
+}
+
+#[test]
+fn test_unknown_command_is_reported() {
+    assert_eq!(
+        Repl::new(ErrorFormat::Human).command(":nope"),
+        "Unknown command ':nope'. Type :help for a list."
+    );
+}
@@ -0,0 +1,83 @@
+mod common;
+
+use common::{global, resolve, run};
+use rslox::literal::Literal;
+
+#[test]
+fn test_closure_binds_to_variable_in_scope_at_declaration() {
+    let interpreter = run("var a = \"global\";
+         var first;
+         var second;
+         {
+             fun showA() { return a; }
+             first = showA();
+             var a = \"block\";
+             second = showA();
+         }")
+    .unwrap();
+
+    assert_eq!(
+        global(&interpreter, "first"),
+        Literal::String("global".to_string())
+    );
+    assert_eq!(
+        global(&interpreter, "second"),
+        Literal::String("global".to_string())
+    );
+}
+
+#[test]
+fn test_assignment_to_resolved_local_in_outer_scope() {
+    let interpreter = run("var result;
+         {
+             var total = 0;
+             {
+                 {
+                     total = total + 5;
+                 }
+             }
+             result = total;
+         }")
+    .unwrap();
+
+    assert_eq!(global(&interpreter, "result"), Literal::Number(5.0));
+}
+
+#[test]
+fn test_read_local_in_own_initializer_is_error() {
+    let errors = resolve("{ var a = 1; { var a = a; } }").err().unwrap();
+
+    assert_eq!(errors.len(), 1);
+    assert_eq!(
+        errors[0].message,
+        "Can't read local variable in its own initializer."
+    );
+}
+
+#[test]
+fn test_duplicate_local_declaration_is_error() {
+    let errors = resolve("fun f(a) { var b; var b; }").err().unwrap();
+
+    assert_eq!(errors.len(), 1);
+    assert_eq!(
+        errors[0].message,
+        "Already a variable with this name in this scope."
+    );
+    assert_eq!(errors[0].token.lexeme(), "b");
+}
+
+#[test]
+fn test_global_redeclaration_is_allowed() {
+    assert!(resolve("var a = 1; var a = a + 1;").is_ok());
+}
+
+#[test]
+fn test_top_level_return_is_error() {
+    let errors = resolve("return 1;").err().unwrap();
+
+    assert_eq!(errors[0].message, "Can't return from top-level code.");
+    assert_eq!(
+        errors[0].to_string(),
+        "[line 1] Error at 'return': Can't return from top-level code."
+    );
+}
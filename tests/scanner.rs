@@ -0,0 +1,265 @@
+use std::rc::Rc;
+
+use rslox::literal::Literal;
+use rslox::scanner::{ScanErrorKind, Scanner};
+use rslox::token_kind::TokenKind;
+
+#[test]
+fn test_scan_tokens_ends_with_eof() {
+    let tokens = Scanner::new("var a = 1;").scan_tokens().unwrap();
+
+    let kinds: Vec<_> = tokens.iter().map(|token| token.kind).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            TokenKind::Var,
+            TokenKind::Identifier,
+            TokenKind::Equal,
+            TokenKind::Number,
+            TokenKind::Semicolon,
+            TokenKind::Eof,
+        ]
+    );
+}
+
+#[test]
+fn test_unexpected_characters_are_collected() {
+    let errors = Scanner::new("var a = 1;\n  @ b # c")
+        .scan_tokens()
+        .unwrap_err();
+
+    assert_eq!(errors.len(), 2);
+    assert_eq!(errors[0].kind, ScanErrorKind::UnexpectedCharacter);
+    assert_eq!((errors[0].line, errors[0].column), (2, 3));
+    assert_eq!(errors[0].text, "@");
+    assert_eq!((errors[1].line, errors[1].column), (2, 7));
+    assert_eq!(errors[1].text, "#");
+    assert_eq!(
+        errors[0].to_string(),
+        "[line 2:3] Error at '@': Unexpected character."
+    );
+}
+
+#[test]
+fn test_unterminated_string_points_at_opening_quote() {
+    let errors = Scanner::new("print 1;\nprint \"abc\ndef")
+        .scan_tokens()
+        .unwrap_err();
+
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].kind, ScanErrorKind::UnterminatedString);
+    assert_eq!((errors[0].line, errors[0].column), (2, 7));
+    assert_eq!(errors[0].text, "\"abc\ndef");
+}
+
+#[test]
+fn test_columns_reset_after_multiline_string() {
+    let errors = Scanner::new("\"a\nbc\" @").scan_tokens().unwrap_err();
+
+    assert_eq!((errors[0].line, errors[0].column), (2, 5));
+}
+
+#[test]
+fn test_unicode_in_strings_comments_and_identifiers() {
+    let source = "// café ☕\nvar naïve = \"héllo wörld\"; print naïve;";
+    let tokens = Scanner::new(source).scan_tokens().unwrap();
+
+    let name = &tokens[1];
+    assert_eq!(name.kind, TokenKind::Identifier);
+    assert_eq!(name.lexeme(), "naïve");
+    assert_eq!((name.line, name.column), (2, 5));
+    assert_eq!(&source[name.start..name.end], "naïve");
+
+    let string = &tokens[3];
+    assert_eq!(string.lexeme(), "\"héllo wörld\"");
+    assert_eq!(string.column, 13);
+
+    let semicolon = &tokens[4];
+    assert_eq!(semicolon.column, 26);
+}
+
+#[test]
+fn test_columns_count_characters_not_bytes() {
+    let errors = Scanner::new("\"ü\" @").scan_tokens().unwrap_err();
+
+    assert_eq!((errors[0].line, errors[0].column), (1, 5));
+    assert_eq!(errors[0].start, 5);
+}
+
+#[test]
+fn test_identifiers_may_contain_underscores() {
+    let tokens = Scanner::new("_a_b1").scan_tokens().unwrap();
+
+    assert_eq!(tokens[0].kind, TokenKind::Identifier);
+    assert_eq!(tokens[0].lexeme(), "_a_b1");
+}
+
+#[test]
+fn test_large_sources_scan_in_linear_time() {
+    let source = "var größe = \"ß\"; // ok\n".repeat(50_000);
+    let tokens = Scanner::new(source).scan_tokens().unwrap();
+
+    assert_eq!(tokens.len(), 5 * 50_000 + 1);
+    assert_eq!(tokens.last().unwrap().line, 50_001);
+}
+
+#[test]
+fn test_tokens_share_the_source_instead_of_copying_lexemes() {
+    let source: Rc<str> = Rc::from("var answer = 42;");
+    let tokens = Scanner::new(Rc::clone(&source)).scan_tokens().unwrap();
+
+    assert!(tokens
+        .iter()
+        .all(|token| Rc::ptr_eq(&token.source, &source)));
+    assert_eq!(tokens[1].lexeme(), "answer");
+    assert_eq!(tokens.last().unwrap().lexeme(), "");
+}
+
+#[test]
+fn test_scanner_yields_tokens_lazily_ending_with_eof() {
+    let mut scanner = Scanner::new("print 1; @");
+
+    let first = scanner.next().unwrap();
+    assert_eq!(first.kind, TokenKind::Print);
+    assert!(scanner.errors().is_empty());
+
+    let rest: Vec<_> = scanner.by_ref().map(|token| token.kind).collect();
+    assert_eq!(
+        rest,
+        vec![TokenKind::Number, TokenKind::Semicolon, TokenKind::Eof]
+    );
+    assert_eq!(scanner.errors().len(), 1);
+    assert!(scanner.next().is_none());
+}
+
+#[test]
+fn test_block_comments_nest_and_count_lines() {
+    let tokens = Scanner::new("/* outer /* inner\n */ still\n comment */ print 1;")
+        .scan_tokens()
+        .unwrap();
+
+    assert_eq!(tokens[0].kind, TokenKind::Print);
+    assert_eq!((tokens[0].line, tokens[0].column), (3, 13));
+}
+
+#[test]
+fn test_unterminated_block_comment_is_an_error() {
+    let errors = Scanner::new("print 1;\n/* open /* nested */\nprint 2;")
+        .scan_tokens()
+        .unwrap_err();
+
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].kind, ScanErrorKind::UnterminatedComment);
+    assert_eq!((errors[0].line, errors[0].column), (2, 1));
+}
+
+#[test]
+fn test_leading_shebang_line_is_ignored() {
+    let tokens = Scanner::new("#!/usr/bin/env rslox\nprint 1;")
+        .scan_tokens()
+        .unwrap();
+
+    assert_eq!(tokens[0].kind, TokenKind::Print);
+    assert_eq!((tokens[0].line, tokens[0].column), (2, 1));
+
+    let errors = Scanner::new("print 1;\n#!not here")
+        .scan_tokens()
+        .unwrap_err();
+    assert_eq!(errors[0].kind, ScanErrorKind::UnexpectedCharacter);
+}
+
+fn string_value(source: &str) -> String {
+    let tokens = Scanner::new(source).scan_tokens().unwrap();
+    match &tokens[0].literal {
+        Some(Literal::String(value)) => value.clone(),
+        other => panic!("expected a string literal, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_string_escapes_are_decoded() {
+    assert_eq!(
+        string_value(r#""a\nb\tc\rd\\e\"f\0g""#),
+        "a\nb\tc\rd\\e\"f\0g"
+    );
+    assert_eq!(string_value(r#""\u{48}\u{e9}\u{1F600}""#), "Hé😀");
+}
+
+#[test]
+fn test_invalid_escapes_point_at_the_backslash() {
+    let errors = Scanner::new("print \"ok\";\nprint \"é\\q \\u{110000} \\u{} \\u41\";")
+        .scan_tokens()
+        .unwrap_err();
+
+    let found: Vec<_> = errors
+        .iter()
+        .map(|err| (err.kind, err.line, err.column, err.text.as_str()))
+        .collect();
+    assert_eq!(
+        found,
+        vec![
+            (ScanErrorKind::InvalidEscape, 2, 9, "\\q"),
+            (ScanErrorKind::InvalidUnicodeEscape, 2, 12, "\\u{110000}"),
+            (ScanErrorKind::InvalidUnicodeEscape, 2, 23, "\\u{}"),
+            (ScanErrorKind::InvalidUnicodeEscape, 2, 28, "\\u"),
+        ]
+    );
+}
+
+#[test]
+fn test_escaped_quote_does_not_end_the_string() {
+    let errors = Scanner::new(r#"print "abc\";"#).scan_tokens().unwrap_err();
+
+    assert_eq!(errors[0].kind, ScanErrorKind::UnterminatedString);
+}
+
+fn number_value(source: &str) -> f64 {
+    let tokens = Scanner::new(source).scan_tokens().unwrap();
+    assert_eq!(tokens.len(), 2, "{}", source);
+    match tokens[0].literal {
+        Some(Literal::Number(value)) => value,
+        ref other => panic!("expected a number literal, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_extended_number_literals() {
+    assert_eq!(number_value("0xFF"), 255.0);
+    assert_eq!(number_value("0XdEaD_BeEf"), 3735928559.0);
+    assert_eq!(number_value("0b1010"), 10.0);
+    assert_eq!(number_value("1e-9"), 1e-9);
+    assert_eq!(number_value("6.02E23"), 6.02e23);
+    assert_eq!(number_value("2.5e+3"), 2500.0);
+    assert_eq!(number_value("1_000_000"), 1_000_000.0);
+    assert_eq!(number_value("1.250_5"), 1.2505);
+    assert_eq!(number_value("0"), 0.0);
+}
+
+#[test]
+fn test_malformed_numbers_are_errors() {
+    for source in [
+        "0x", "0b", "1e", "1e+", "0b102", "0xFG", "1__0", "1_", "1_.5", "12px",
+    ] {
+        let errors = Scanner::new(source).scan_tokens().unwrap_err();
+
+        assert_eq!(errors.len(), 1, "{}", source);
+        assert_eq!(errors[0].kind, ScanErrorKind::MalformedNumber, "{}", source);
+        assert_eq!(errors[0].text, source);
+    }
+}
+
+#[test]
+fn test_dot_after_number_without_digits_is_not_a_fraction() {
+    let tokens = Scanner::new("1.foo").scan_tokens().unwrap();
+
+    let kinds: Vec<_> = tokens.iter().map(|token| token.kind).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            TokenKind::Number,
+            TokenKind::Dot,
+            TokenKind::Identifier,
+            TokenKind::Eof
+        ]
+    );
+}
@@ -0,0 +1,83 @@
+use rslox::parser::Parser;
+use rslox::scanner::Scanner;
+use rslox::span::Span;
+use rslox::statement::Stmt;
+
+fn parse(source: &str) -> Vec<Stmt> {
+    let tokens = Scanner::new(source).scan_tokens().unwrap();
+    Parser::new(tokens).parse().unwrap()
+}
+
+fn text(source: &str, span: Span) -> &str {
+    &source[span.start..span.end]
+}
+
+#[test]
+fn test_tokens_carry_offsets_and_columns() {
+    let tokens = Scanner::new("var answer = 42;\n  print answer;")
+        .scan_tokens()
+        .unwrap();
+
+    let answer = &tokens[1];
+    assert_eq!((answer.start, answer.end, answer.column), (4, 10, 5));
+
+    let print = &tokens[5];
+    assert_eq!(print.lexeme(), "print");
+    assert_eq!((print.line, print.column, print.start), (2, 3, 19));
+
+    let eof = tokens.last().unwrap();
+    assert_eq!((eof.start, eof.end, eof.line, eof.column), (32, 32, 2, 16));
+}
+
+#[test]
+fn test_multiline_string_token_starts_on_its_first_line() {
+    let tokens = Scanner::new("x = \"a\nb\";").scan_tokens().unwrap();
+
+    let string = &tokens[2];
+    assert_eq!((string.line, string.column), (1, 5));
+    assert_eq!((string.start, string.end), (4, 9));
+}
+
+#[test]
+fn test_expression_spans_cover_their_operands() {
+    let source = "print (1 + 2) * foo.bar(3);";
+    let statements = parse(source);
+
+    let Stmt::Print(expr, span) = &statements[0] else {
+        panic!("Expected a print statement");
+    };
+    assert_eq!(text(source, *span), source);
+    assert_eq!(text(source, expr.span()), "(1 + 2) * foo.bar(3)");
+}
+
+#[test]
+fn test_statement_spans_cover_whole_constructs() {
+    let source = "class A < B {\n  m() { return 1; }\n}\nif (x) {\n  y = 1;\n} else z;";
+    let statements = parse(source);
+
+    assert_eq!(
+        text(source, statements[0].span()),
+        "class A < B {\n  m() { return 1; }\n}"
+    );
+    let Stmt::Class(_, _, methods, _) = &statements[0] else {
+        panic!("Expected a class declaration");
+    };
+    assert_eq!(text(source, methods[0].span()), "m() { return 1; }");
+
+    let span = statements[1].span();
+    assert_eq!(text(source, span), "if (x) {\n  y = 1;\n} else z;");
+    assert_eq!((span.line, span.column), (4, 1));
+}
+
+#[test]
+fn test_desugared_for_loop_uses_span_of_for_statement() {
+    let source = "for (var i = 0; i < 3; i = i + 1) print i;";
+    let statements = parse(source);
+
+    let Stmt::Block(body, span) = &statements[0] else {
+        panic!("Expected the for loop to desugar into a block");
+    };
+    assert_eq!(text(source, *span), source);
+    assert_eq!(text(source, body[0].span()), "var i = 0;");
+    assert_eq!(text(source, body[1].span()), source);
+}